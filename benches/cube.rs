@@ -0,0 +1,66 @@
+// The cube is always a 3x3, so these measure it at that size only. Benchmarks over cube sizes wait
+// for NxN cubes.
+
+use std::hint::black_box;
+
+use bevy::prelude::*;
+use criterion::{Criterion, criterion_group, criterion_main};
+use ruvik::{
+    CubeAxis,
+    bench_support::{PickingCube, is_turning, rotate_position, start_turn, turning_app},
+};
+
+fn rotate_position_benchmark(c: &mut Criterion) {
+    c.bench_function("rotate_position", |b| {
+        b.iter(|| {
+            for axis in [CubeAxis::X, CubeAxis::Y, CubeAxis::Z] {
+                for i in 0..27 {
+                    let position = (i / 9, i / 3 % 3, i % 3);
+                    black_box(rotate_position(black_box(position), axis, black_box(1)));
+                }
+            }
+        });
+    });
+}
+
+// A grid of rays from where the head-on camera sits, most of them hitting the cube
+fn picking_benchmark(c: &mut Criterion) {
+    let cube = PickingCube::new();
+    let origin = Vec3::new(0.0, 0.0, 3.0);
+    let targets = (0..16)
+        .flat_map(|x| {
+            (0..16).map(move |y| Vec3::new(x as f32 / 15.0 - 0.5, y as f32 / 15.0 - 0.5, 0.0))
+        })
+        .collect::<Vec<_>>();
+
+    c.bench_function("pick_cubie", |b| {
+        b.iter(|| {
+            for target in &targets {
+                black_box(cube.pick(black_box(origin), *target - origin));
+            }
+        });
+    });
+}
+
+// Every frame of a quarter turn, from the layer being let go until it settles in its new slots
+fn cubie_rotation_benchmark(c: &mut Criterion) {
+    let mut app = turning_app();
+
+    c.bench_function("cubie_rotation_system quarter turn", |b| {
+        b.iter(|| {
+            start_turn(&mut app, CubeAxis::Y, 2);
+            app.update();
+            while is_turning(&mut app) {
+                app.update();
+            }
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    rotate_position_benchmark,
+    picking_benchmark,
+    cubie_rotation_benchmark
+);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+//! Game internals the benchmarks in `benches/` measure. Only built with the `bench` feature, none
+//! of this is part of the game's API.
+
+use bevy::prelude::*;
+
+use super::test_support::{fixed_clock_app, spawn_test_cube, test_cubies};
+
+use super::{
+    ActiveCubeRotation, BeingDragged, Cube, CubeAxis, Cubie, CubieBounds, LastMoveCompletion,
+    ROTATION_SPEED, cubie_rotation_system, layer_index, picking::pick_cubie,
+};
+
+/// Slot a cubie in `position` ends up in after the turn.
+pub fn rotate_position(
+    position: (u32, u32, u32),
+    axis: CubeAxis,
+    quarter_turns: u32,
+) -> (u32, u32, u32) {
+    super::rotate_position(position, axis, quarter_turns)
+}
+
+/// A solved 3x3's cubies, for casting rays at.
+pub struct PickingCube(Vec<(Cubie, Transform, CubieBounds)>);
+
+impl PickingCube {
+    pub fn new() -> Self {
+        Self(test_cubies().collect())
+    }
+
+    /// Slot of the closest cubie the ray hits, given in the cube's local space.
+    pub fn pick(&self, origin: Vec3, direction: Vec3) -> Option<(u32, u32, u32)> {
+        pick_cubie(
+            origin,
+            direction,
+            self.0
+                .iter()
+                .map(|(cubie, transform, bounds)| (cubie, transform, bounds)),
+        )
+        .map(|pick| pick.position)
+    }
+}
+
+impl Default for PickingCube {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Headless app that only animates layer turns, with a solved cube and a fixed 60 fps clock.
+pub fn turning_app() -> App {
+    let mut app = fixed_clock_app(1.0 / 60.0);
+    app.init_resource::<LastMoveCompletion>()
+        .add_systems(Update, cubie_rotation_system);
+
+    spawn_test_cube(app.world_mut());
+
+    app
+}
+
+/// Starts a quarter turn of a layer in an app from [`turning_app`].
+pub fn start_turn(app: &mut App, axis: CubeAxis, layer: u32) {
+    let world = app.world_mut();
+    let layer_cubies = world
+        .query::<(Entity, &Cubie, &Transform)>()
+        .iter(world)
+        .filter(|(_, cubie, _)| layer_index(cubie.position, axis) == layer)
+        .map(|(entity, _, transform)| (entity, transform.rotation))
+        .collect::<Vec<_>>();
+    for (entity, rotation) in layer_cubies {
+        world.entity_mut(entity).insert(BeingDragged {
+            prev_rotation: rotation,
+        });
+    }
+
+    let cube = world
+        .query_filtered::<Entity, With<Cube>>()
+        .single(world)
+        .unwrap();
+    world.entity_mut(cube).insert(ActiveCubeRotation {
+        axis,
+        current_angle: 0.0,
+        target_rotations: 1,
+        speed: ROTATION_SPEED,
+        overshoot: 0.0,
+    });
+}
+
+/// Whether the app's cube has a layer turn still settling.
+pub fn is_turning(app: &mut App) -> bool {
+    let world = app.world_mut();
+    world
+        .query_filtered::<(), (With<Cube>, With<ActiveCubeRotation>)>()
+        .iter(world)
+        .next()
+        .is_some()
+}
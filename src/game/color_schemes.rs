@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    preferences::{ColorScheme, CubeShape, Preferences, StickerColor},
+};
+
+use super::{
+    Cubie, cube_vertex_colors, cubie_face_colors,
+    hud::{Toast, show_toast},
+};
+
+// Scheme the game was started with, so cycling through the presets can come back to it
+#[derive(Resource)]
+pub(super) struct StartingColorScheme(ColorScheme);
+
+const CYCLE_COLOR_SCHEME_KEY: KeyCode = KeyCode::KeyP;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Game), starting_color_scheme_setup);
+}
+
+fn starting_color_scheme_setup(mut commands: Commands, preferences: Res<Preferences>) {
+    commands.insert_resource(StartingColorScheme(preferences.color_scheme.clone()));
+}
+
+pub(super) fn color_scheme_presets() -> [(&'static str, ColorScheme); 2] {
+    [
+        ("Standard", ColorScheme::default()),
+        // White opposite blue and yellow opposite green
+        (
+            "Japanese",
+            ColorScheme {
+                neg_x: StickerColor::Blue,
+                neg_y: StickerColor::Yellow,
+                ..default()
+            },
+        ),
+    ]
+}
+
+// Presets in order, led by the starting scheme when it's one the player made
+pub(super) fn color_scheme_cycle(starting: &ColorScheme) -> Vec<(&'static str, ColorScheme)> {
+    let presets = color_scheme_presets();
+    let custom = (!presets.iter().any(|(_, scheme)| scheme == starting))
+        .then(|| ("Your colors", starting.clone()));
+
+    custom.into_iter().chain(presets).collect()
+}
+
+// Recolors the cubies in place, each keeps the stickers of the slot it started in
+pub(super) fn cycle_color_scheme_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    starting: Res<StartingColorScheme>,
+    mut preferences: ResMut<Preferences>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    cubies: Query<(&Cubie, &Mesh3d)>,
+    toast: Single<(&mut Toast, &mut Text, &mut Visibility)>,
+) {
+    if !keys.just_pressed(CYCLE_COLOR_SCHEME_KEY) || preferences.cube_shape == CubeShape::Mirror {
+        return;
+    }
+
+    let cycle = color_scheme_cycle(&starting.0);
+    let next = cycle
+        .iter()
+        .position(|(_, scheme)| *scheme == preferences.color_scheme)
+        .map_or(0, |index| (index + 1) % cycle.len());
+    let (name, color_scheme) = cycle[next].clone();
+
+    for (cubie, mesh) in &cubies {
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            let colors = cubie_face_colors(
+                preferences.cube_shape,
+                &color_scheme,
+                preferences.sticker_calibration,
+                cubie.home_position,
+            );
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, cube_vertex_colors(&colors));
+        }
+    }
+
+    preferences.color_scheme = color_scheme;
+    show_toast(toast, format!("Colors: {name}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_scheme_cycle_keeps_the_players_own_scheme() {
+        use crate::preferences::StickerColor;
+
+        let custom = ColorScheme {
+            pos_x: StickerColor::Red,
+            pos_z: StickerColor::White,
+            ..default()
+        };
+        let names = |starting: &ColorScheme| {
+            color_scheme_cycle(starting)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&custom), ["Your colors", "Standard", "Japanese"]);
+        assert_eq!(names(&ColorScheme::default()), ["Standard", "Japanese"]);
+        assert!(
+            color_scheme_presets()
+                .iter()
+                .all(|(_, scheme)| scheme.has_distinct_colors())
+        );
+    }
+}
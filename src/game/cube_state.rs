@@ -0,0 +1,246 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use super::{CubeAxis, CubeFace, Move, MoveError, is_outer_face};
+
+/// Stickers of a cube, without any of the animation or input state of the one being played.
+///
+/// Faces are read in `(u, v)` coordinates: `u` counts columns from left to right and `v` rows
+/// from bottom to top, looking at the face from outside the cube. The side faces have the top
+/// face above them, the top face has the back face above it and the bottom face has the front.
+///
+/// ```
+/// use ruvik::{CubeAxis, CubeFace, CubeState, Move};
+///
+/// let solved = CubeState::solved(3);
+/// assert_eq!(solved.facelet(CubeFace::PosZ, 0, 0), CubeFace::PosZ);
+///
+/// // A counterclockwise quarter turn of the top layer, seen from above
+/// let mut cube = solved.clone();
+/// cube.apply_move(Move { axis: CubeAxis::Y, layer: 2, quarter_turns: 1 }).unwrap();
+///
+/// // The top row of the left face moved onto the front face, the rest of the front stayed
+/// assert_eq!(cube.facelet(CubeFace::PosZ, 0, 2), CubeFace::NegX);
+/// assert_eq!(cube.facelet(CubeFace::PosZ, 2, 1), CubeFace::PosZ);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CubeState {
+    size: u32,
+    stickers: Vec<CubeFace>, // Face each sticker belongs to when solved, face by face and row by row
+}
+
+impl CubeState {
+    /// A solved cube with `size` layers along each axis.
+    ///
+    /// Panics if `size` is zero.
+    pub fn solved(size: u32) -> Self {
+        assert!(size > 0, "a cube needs at least one layer");
+
+        let stickers = CubeFace::ALL
+            .into_iter()
+            .flat_map(|face| std::iter::repeat_n(face, (size * size) as usize))
+            .collect();
+
+        Self { size, stickers }
+    }
+
+    /// Number of layers along each axis.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Face the sticker at `(u, v)` on `face` is on when the cube is solved. That's its color
+    /// whatever the color scheme, the scheme only decides what each face's color looks like.
+    ///
+    /// Panics if `u` or `v` is past the last row or column.
+    pub fn facelet(&self, face: CubeFace, u: u32, v: u32) -> CubeFace {
+        assert!(
+            u < self.size && v < self.size,
+            "({u}, {v}) is outside a face of a cube with {} layers",
+            self.size
+        );
+
+        self.stickers[self.index(face, u, v)]
+    }
+
+    /// Turns a layer the same way [`try_apply_move`](crate::try_apply_move) turns the cube being
+    /// played.
+    pub fn apply_move(&mut self, player_move: Move) -> Result<(), MoveError> {
+        if player_move.layer >= self.size {
+            return Err(MoveError::InvalidLayer(player_move.layer));
+        }
+
+        let rotation = Quat::from_axis_angle(
+            player_move.axis.unit(),
+            (PI / 2.0) * (player_move.quarter_turns % 4) as f32,
+        );
+
+        let mut stickers = self.stickers.clone();
+        for face in CubeFace::ALL {
+            for u in 0..self.size {
+                for v in 0..self.size {
+                    let position = self.facelet_position(face, u, v);
+                    let layer = match player_move.axis {
+                        CubeAxis::X => position.x,
+                        CubeAxis::Y => position.y,
+                        CubeAxis::Z => position.z,
+                    };
+                    if layer != player_move.layer {
+                        continue;
+                    }
+
+                    // Rotated around the cube's center, in doubled coordinates to stay on whole numbers
+                    let centered = 2 * position.as_ivec3() - (self.size as i32 - 1);
+                    let rotated = (rotation * centered.as_vec3()).round().as_ivec3();
+                    let new_position = ((rotated + (self.size as i32 - 1)) / 2).as_uvec3();
+                    let new_face = CubeFace::from_normal(rotation * face.normal());
+
+                    let (new_u, new_v) = self.facelet_coords(new_face, new_position);
+                    stickers[self.index(new_face, new_u, new_v)] =
+                        self.stickers[self.index(face, u, v)];
+                }
+            }
+        }
+
+        self.stickers = stickers;
+        Ok(())
+    }
+
+    /// 64-bit FNV-1a hash of the size and of where every sticker is. Equal states always hash the
+    /// same, and different states share a hash only with negligible probability, so callers that
+    /// need exact equality should compare the states with `==`. The value doesn't depend on the
+    /// run or the platform, it can be stored and compared later.
+    pub fn hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let stickers = self.stickers.iter().map(|face| face_index(*face) as u8);
+        self.size
+            .to_le_bytes()
+            .into_iter()
+            .chain(stickers)
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    // The cube being played, from the slot each cubie is in and how it's turned from where it
+    // started. Every sticker reads as the face it points away from when solved.
+    pub(super) fn from_cubies(cubies: impl IntoIterator<Item = ((u32, u32, u32), Quat)>) -> Self {
+        let mut state = Self::solved(3);
+        for (position, rotation) in cubies {
+            for face in CubeFace::ALL {
+                if !is_outer_face(position, face) {
+                    continue;
+                }
+
+                let (u, v) = state.facelet_coords(face, UVec3::from(position));
+                let index = state.index(face, u, v);
+                state.stickers[index] = CubeFace::from_normal(rotation.inverse() * face.normal());
+            }
+        }
+
+        state
+    }
+
+    fn index(&self, face: CubeFace, u: u32, v: u32) -> usize {
+        ((face_index(face) as u32 * self.size + v) * self.size + u) as usize
+    }
+
+    // Cubie the sticker is on, (0, 0, 0) being the left-bottom-back corner like the game's cubies
+    fn facelet_position(&self, face: CubeFace, u: u32, v: u32) -> UVec3 {
+        let last = self.size - 1;
+        match face {
+            CubeFace::PosX => UVec3::new(last, v, last - u),
+            CubeFace::NegX => UVec3::new(0, v, u),
+            CubeFace::PosY => UVec3::new(u, last, last - v),
+            CubeFace::NegY => UVec3::new(u, 0, v),
+            CubeFace::PosZ => UVec3::new(u, v, last),
+            CubeFace::NegZ => UVec3::new(last - u, v, 0),
+        }
+    }
+
+    fn facelet_coords(&self, face: CubeFace, position: UVec3) -> (u32, u32) {
+        let last = self.size - 1;
+        match face {
+            CubeFace::PosX => (last - position.z, position.y),
+            CubeFace::NegX => (position.z, position.y),
+            CubeFace::PosY => (position.x, last - position.z),
+            CubeFace::NegY => (position.x, position.z),
+            CubeFace::PosZ => (position.x, position.y),
+            CubeFace::NegZ => (last - position.x, position.y),
+        }
+    }
+}
+
+fn face_index(face: CubeFace) -> usize {
+    CubeFace::ALL.iter().position(|f| *f == face).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::legal_moves;
+
+    #[test]
+    fn cube_state_hashes_tell_states_apart() {
+        assert_eq!(CubeState::solved(3).hash(), CubeState::solved(3).hash());
+        assert_ne!(CubeState::solved(2).hash(), CubeState::solved(3).hash());
+
+        // Pinned, stored hashes have to keep matching
+        assert_eq!(CubeState::solved(3).hash(), 0x5649_040f_8560_b651);
+
+        for player_move in legal_moves(3) {
+            let mut cube = CubeState::solved(3);
+            cube.apply_move(player_move).unwrap();
+            assert_ne!(cube.hash(), CubeState::solved(3).hash(), "{player_move:?}");
+
+            cube.apply_move(player_move.inverse()).unwrap();
+            assert_eq!(cube.hash(), CubeState::solved(3).hash(), "{player_move:?}");
+        }
+    }
+
+    #[test]
+    fn cube_state_turns_come_back_around() {
+        for size in 1..=4 {
+            let solved = CubeState::solved(size);
+            for axis in [CubeAxis::X, CubeAxis::Y, CubeAxis::Z] {
+                for layer in 0..size {
+                    let quarter_turn = Move {
+                        axis,
+                        layer,
+                        quarter_turns: 1,
+                    };
+
+                    let mut cube = solved.clone();
+                    cube.apply_move(quarter_turn).unwrap();
+                    assert_ne!(cube, solved, "{quarter_turn:?} on size {size}");
+
+                    for _ in 0..3 {
+                        cube.apply_move(quarter_turn).unwrap();
+                    }
+                    assert_eq!(cube, solved, "four of {quarter_turn:?} on size {size}");
+
+                    // Turning the other way undoes it
+                    cube.apply_move(quarter_turn).unwrap();
+                    cube.apply_move(Move {
+                        quarter_turns: 3,
+                        ..quarter_turn
+                    })
+                    .unwrap();
+                    assert_eq!(cube, solved);
+                }
+            }
+
+            assert_eq!(
+                solved.clone().apply_move(Move {
+                    axis: CubeAxis::X,
+                    layer: size,
+                    quarter_turns: 1,
+                }),
+                Err(MoveError::InvalidLayer(size))
+            );
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::{
+    prelude::*,
+    window::{CursorIcon, CursorOptions, CustomCursor, CustomCursorImage, PrimaryWindow},
+};
+
+use crate::{GameState, preferences::Preferences};
+
+use super::{ActiveDrag, Cube};
+
+const CUSTOM_CURSOR_PATH: &str = "textures/cursor.png";
+const CUSTOM_CURSOR_HOTSPOT: (u16, u16) = (16, 16); // Center of the ring
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Game), cursor_setup)
+        .add_systems(OnExit(GameState::Game), cursor_cleanup);
+}
+
+fn cursor_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    preferences: Res<Preferences>,
+    window: Single<Entity, With<PrimaryWindow>>,
+) {
+    if !preferences.custom_cursor {
+        return;
+    }
+
+    commands
+        .entity(*window)
+        .insert(CursorIcon::Custom(CustomCursor::Image(CustomCursorImage {
+            handle: asset_server.load(CUSTOM_CURSOR_PATH),
+            hotspot: CUSTOM_CURSOR_HOTSPOT,
+            ..default()
+        })));
+}
+
+// Menus always get the OS cursor back, whatever the game left it as
+fn cursor_cleanup(
+    mut commands: Commands,
+    window: Single<(Entity, &mut CursorOptions), With<PrimaryWindow>>,
+) {
+    let (window_entity, mut cursor_options) = window.into_inner();
+    cursor_options.visible = true;
+    commands.entity(window_entity).insert(CursorIcon::default());
+}
+
+pub(super) fn hide_cursor_while_dragging_system(
+    preferences: Res<Preferences>,
+    dragging: Single<Has<ActiveDrag>, With<Cube>>,
+    mut cursor_options: Single<&mut CursorOptions, With<PrimaryWindow>>,
+) {
+    let visible = !(preferences.hide_cursor_while_dragging && *dragging);
+    if cursor_options.visible != visible {
+        cursor_options.visible = visible;
+    }
+}
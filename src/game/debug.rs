@@ -0,0 +1,269 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    GameState, MainFont,
+    preferences::{ColorScheme, CubeShape, Preferences},
+};
+
+use super::{
+    Cube, CubeFace, Cubie, CubieBounds, PickFailure, cursor_pick, face_sticker, is_outer_face,
+};
+
+#[derive(Resource, Default)]
+pub(super) struct DebugOptions {
+    pub(super) show_axes: bool,
+    pub(super) show_cubie_info: bool,
+    pub(super) verbose_picking: bool,
+    pub(super) show_cubie_indices: bool,
+}
+
+// Drawn on top of the cube so the axes stay visible through it
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub(super) struct DebugGizmos;
+
+#[derive(Component)]
+pub(super) struct CubieTooltip;
+
+// Logical slot of a cubie, floating over it to check positions after turns
+#[derive(Component)]
+pub(super) struct CubieIndexLabel(Entity); // The cubie it labels
+
+const TOGGLE_AXES_KEY: KeyCode = KeyCode::F1;
+const TOGGLE_CUBIE_INFO_KEY: KeyCode = KeyCode::F2;
+const TOGGLE_VERBOSE_PICKING_KEY: KeyCode = KeyCode::F3;
+const TOGGLE_CUBIE_INDICES_KEY: KeyCode = KeyCode::F4;
+
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0); // From the cursor, so it doesn't cover the hovered cubie
+const TOOLTIP_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.75);
+
+const CUBIE_INDEX_FONT_SIZE: f32 = 12.0;
+
+const AXIS_LENGTH: f32 = 0.9; // In cube units, pokes out of the cube's faces
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<DebugOptions>()
+        .insert_gizmo_config(
+            DebugGizmos,
+            GizmoConfig {
+                depth_bias: -1.0,
+                ..default()
+            },
+        )
+        .add_systems(OnEnter(GameState::Game), cubie_tooltip_setup);
+}
+
+fn cubie_tooltip_setup(mut commands: Commands, font_family: Res<MainFont>) {
+    commands.spawn((
+        DespawnOnExit(GameState::Game),
+        CubieTooltip,
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(px(6)),
+            ..default()
+        },
+        BackgroundColor(TOOLTIP_BACKGROUND_COLOR),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        children![(
+            Text::default(),
+            TextFont {
+                font_size: 16.0,
+                font: font_family.0.clone(),
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        )],
+    ));
+}
+
+pub(super) fn toggle_debug_options_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut debug_options: ResMut<DebugOptions>,
+) {
+    if keys.just_pressed(TOGGLE_AXES_KEY) {
+        debug_options.show_axes = !debug_options.show_axes;
+    }
+
+    if keys.just_pressed(TOGGLE_CUBIE_INFO_KEY) {
+        debug_options.show_cubie_info = !debug_options.show_cubie_info;
+    }
+
+    if keys.just_pressed(TOGGLE_CUBIE_INDICES_KEY) {
+        debug_options.show_cubie_indices = !debug_options.show_cubie_indices;
+    }
+
+    if keys.just_pressed(TOGGLE_VERBOSE_PICKING_KEY) {
+        debug_options.verbose_picking = !debug_options.verbose_picking;
+        info!(
+            "Verbose picking {}",
+            if debug_options.verbose_picking {
+                "on"
+            } else {
+                "off"
+            }
+        );
+    }
+}
+
+// Explains why a click didn't start a layer drag, so picking issues can be reported
+pub(super) fn log_pick_failure(debug_options: &DebugOptions, failure: PickFailure) {
+    if debug_options.verbose_picking {
+        info!("Layer drag not started: {failure}");
+    }
+}
+
+pub(super) fn draw_cube_axes_system(
+    debug_options: Res<DebugOptions>,
+    cube_transform: Single<&GlobalTransform, With<Cube>>,
+    mut gizmos: Gizmos<DebugGizmos>,
+) {
+    if !debug_options.show_axes {
+        return;
+    }
+
+    let origin = cube_transform.translation();
+
+    for (axis, color) in [
+        (Vec3::X, Color::srgb(1.0, 0.0, 0.0)),
+        (Vec3::Y, Color::srgb(0.0, 1.0, 0.0)),
+        (Vec3::Z, Color::srgb(0.0, 0.0, 1.0)),
+    ] {
+        let end = cube_transform.transform_point(axis * AXIS_LENGTH);
+        gizmos.arrow(origin, end, color);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn cubie_tooltip_system(
+    debug_options: Res<DebugOptions>,
+    preferences: Res<Preferences>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube_transform: Single<&GlobalTransform, With<Cube>>,
+    cubies: Query<(&Cubie, &Transform, &CubieBounds)>,
+    tooltip: Single<(&mut Node, &mut Visibility, &Children), With<CubieTooltip>>,
+    mut texts: Query<&mut Text>,
+) {
+    let (mut node, mut visibility, children) = tooltip.into_inner();
+
+    let hovered = debug_options
+        .show_cubie_info
+        .then(|| window.cursor_position())
+        .flatten()
+        .and_then(|cursor_position| {
+            let (camera, camera_transform) = *camera;
+            let pick = cursor_pick(
+                cursor_position,
+                camera,
+                camera_transform,
+                *cube_transform,
+                cubies,
+            )
+            .ok()?;
+            let (cubie, _, _) = cubies
+                .iter()
+                .find(|(cubie, _, _)| cubie.position == pick.position)?;
+            Some((cursor_position, cubie))
+        });
+
+    let Some((cursor_position, cubie)) = hovered else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let (x, y, z) = cubie.position;
+    let stickers = sticker_names(
+        preferences.cube_shape,
+        &preferences.color_scheme,
+        cubie.home_position,
+    )
+    .join(", ");
+
+    for &child in children {
+        if let Ok(mut text) = texts.get_mut(child) {
+            text.0 = format!("Position: ({x}, {y}, {z})\nStickers: {stickers}");
+        }
+    }
+
+    node.left = px(cursor_position.x + TOOLTIP_OFFSET.x);
+    node.top = px(cursor_position.y + TOOLTIP_OFFSET.y);
+    visibility.set_if_neq(Visibility::Inherited);
+}
+
+// Labels are spawned the first time they're shown, as the cubies don't exist yet when the game starts
+pub(super) fn cubie_index_labels_system(
+    mut commands: Commands,
+    debug_options: Res<DebugOptions>,
+    font_family: Res<MainFont>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cubies: Query<(Entity, &Cubie, &GlobalTransform)>,
+    mut labels: Query<(Entity, &CubieIndexLabel, &mut Node, &mut Text)>,
+) {
+    if !debug_options.show_cubie_indices {
+        for (label_entity, _, _, _) in &labels {
+            commands.entity(label_entity).despawn();
+        }
+        return;
+    }
+
+    if labels.is_empty() {
+        for (cubie_entity, _, _) in &cubies {
+            commands.spawn((
+                DespawnOnExit(GameState::Game),
+                CubieIndexLabel(cubie_entity),
+                Node {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                Text::default(),
+                TextFont {
+                    font_size: CUBIE_INDEX_FONT_SIZE,
+                    font: font_family.0.clone(),
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BackgroundColor(TOOLTIP_BACKGROUND_COLOR),
+                Pickable::IGNORE,
+            ));
+        }
+        return;
+    }
+
+    let (camera, camera_transform) = *camera;
+
+    for (_, label, mut node, mut text) in &mut labels {
+        let Ok((_, cubie, cubie_transform)) = cubies.get(label.0) else {
+            continue;
+        };
+        let Ok(viewport_position) =
+            camera.world_to_viewport(camera_transform, cubie_transform.translation())
+        else {
+            continue;
+        };
+
+        let (x, y, z) = cubie.position;
+        let index = format!("({x}, {y}, {z})");
+        if text.0 != index {
+            text.0 = index;
+        }
+
+        node.left = px(viewport_position.x);
+        node.top = px(viewport_position.y);
+    }
+}
+
+// Stickers follow the cubie around, so they're given by where it sits on a solved cube
+fn sticker_names(
+    shape: CubeShape,
+    color_scheme: &ColorScheme,
+    home_position: (u32, u32, u32),
+) -> Vec<&'static str> {
+    CubeFace::ALL
+        .into_iter()
+        .filter(|face| is_outer_face(home_position, *face))
+        .map(|face| match shape {
+            CubeShape::Standard => face_sticker(color_scheme, face).name(),
+            CubeShape::Mirror => "mirror",
+        })
+        .collect()
+}
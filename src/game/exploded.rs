@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{Cubie, IdleCube, slot_translation};
+
+// Cubies pushed apart from the center to show how the cube is put together. Layers can't be
+// turned while it's exploded, the cubies are only where their slots are once it's collapsed.
+#[derive(Resource, Default)]
+pub(super) struct ExplodedView {
+    exploded: bool,
+    progress: f32, // 0 is collapsed, 1 is fully exploded
+}
+
+impl ExplodedView {
+    pub(super) fn blocks_moves(&self) -> bool {
+        self.exploded || self.progress > 0.0
+    }
+}
+
+const TOGGLE_EXPLODED_VIEW_KEY: KeyCode = KeyCode::KeyE;
+
+const EXPLODE_SECS: f32 = 0.4;
+const EXPLODED_SPREAD: f32 = 0.8; // Extra distance from the center, relative to a cubie's slot
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ExplodedView>()
+        .add_systems(OnExit(GameState::Game), reset_exploded_view);
+}
+
+pub(super) fn toggle_exploded_view_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    // Only toggles while no layer is moving
+    _idle_cube: Single<(), IdleCube>,
+    mut exploded_view: ResMut<ExplodedView>,
+) {
+    if keys.just_pressed(TOGGLE_EXPLODED_VIEW_KEY) {
+        exploded_view.exploded = !exploded_view.exploded;
+    }
+}
+
+pub(super) fn exploded_view_system(
+    time: Res<Time>,
+    mut exploded_view: ResMut<ExplodedView>,
+    mut cubies: Query<(&Cubie, &mut Transform)>,
+) {
+    let target = if exploded_view.exploded { 1.0 } else { 0.0 };
+    if exploded_view.progress == target {
+        return;
+    }
+
+    let step = time.delta_secs() / EXPLODE_SECS;
+    exploded_view.progress = if exploded_view.exploded {
+        (exploded_view.progress + step).min(1.0)
+    } else {
+        (exploded_view.progress - step).max(0.0)
+    };
+
+    let spread = 1.0 + EXPLODED_SPREAD * smoothstep(exploded_view.progress);
+    for (cubie, mut cubie_transform) in &mut cubies {
+        cubie_transform.translation = slot_translation(cubie.position) * spread;
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn reset_exploded_view(mut exploded_view: ResMut<ExplodedView>) {
+    *exploded_view = ExplodedView::default();
+}
@@ -0,0 +1,79 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{GameState, preferences::Preferences};
+
+use super::{ActiveDrag, BeingDragged, Cube, Cubie, release_target, slot_translation};
+
+// Faint copy of a dragged cubie where it would land if the drag was let go now
+#[derive(Component)]
+pub(super) struct LayerGhost(Entity); // The cubie it previews
+
+#[derive(Resource)]
+pub(super) struct GhostMaterial(Handle<StandardMaterial>);
+
+const GHOST_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.3); // Tints the sticker colors
+const GHOST_SCALE: f32 = 1.01; // Slightly larger so it doesn't z-fight with the cubies next to it
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Game), ghost_preview_setup);
+}
+
+fn ghost_preview_setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(GhostMaterial(materials.add(StandardMaterial {
+        base_color: GHOST_COLOR,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    })));
+}
+
+pub(super) fn ghost_preview_system(
+    mut commands: Commands,
+    preferences: Res<Preferences>,
+    ghost_material: Res<GhostMaterial>,
+    cube: Single<(Entity, Option<&ActiveDrag>), With<Cube>>,
+    dragged_cubies: Query<(Entity, &Cubie, &Mesh3d, &Transform, &BeingDragged)>,
+    mut ghosts: Query<(Entity, &LayerGhost, &mut Transform), Without<Cubie>>,
+) {
+    let (cube_entity, active_drag) = *cube;
+
+    let Some(active_drag) = active_drag.filter(|_| preferences.ghost_preview) else {
+        for (ghost_entity, _, _) in &ghosts {
+            commands.entity(ghost_entity).despawn();
+        }
+        return;
+    };
+
+    let (target_rotations, _) = release_target(active_drag, &preferences);
+    let rotation = Quat::from_axis_angle(
+        active_drag.axis.unit(),
+        (PI / 2.0) * target_rotations as f32,
+    );
+
+    let landing_transform =
+        |cubie: &Cubie, cubie_transform: &Transform, being_dragged: &BeingDragged| Transform {
+            translation: rotation * slot_translation(cubie.position),
+            rotation: rotation * being_dragged.prev_rotation,
+            scale: cubie_transform.scale * GHOST_SCALE,
+        };
+
+    if ghosts.is_empty() {
+        for (cubie_entity, cubie, mesh, cubie_transform, being_dragged) in &dragged_cubies {
+            commands.entity(cube_entity).with_child((
+                LayerGhost(cubie_entity),
+                mesh.clone(),
+                MeshMaterial3d(ghost_material.0.clone()),
+                landing_transform(cubie, cubie_transform, being_dragged),
+            ));
+        }
+        return;
+    }
+
+    for (_, ghost, mut ghost_transform) in &mut ghosts {
+        if let Ok((_, cubie, _, cubie_transform, being_dragged)) = dragged_cubies.get(ghost.0) {
+            *ghost_transform = landing_transform(cubie, cubie_transform, being_dragged);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameState, MainFont,
+    preferences::{ControlScheme, Preferences},
+};
+
+use super::{
+    ActiveCubeRotation, Cube, CubeFace, LastMoveCompletion, PendingTurn, WideTurn,
+    exploded::ExplodedView, move_on_cooldown,
+};
+
+// On-screen chrome, all of it is hidden in zen mode
+#[derive(Component)]
+pub(super) struct Hud;
+
+// Notation letter floating over one of the cube's faces, named after where the face points on screen
+#[derive(Component)]
+pub(super) struct FaceLabel(CubeFace);
+
+// Says why layer moves can't start, shown only while they can't
+#[derive(Component)]
+pub(super) struct MoveLockIndicator;
+
+// Short notice at the top of the screen, hidden again when its timer runs out
+#[derive(Component)]
+pub(super) struct Toast(pub(super) Timer);
+
+const TOGGLE_ZEN_MODE_KEY: KeyCode = KeyCode::KeyZ;
+
+const HUD_TEXT_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+const FACE_LABEL_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+const TOAST_SECS: f32 = 1.5;
+
+const FACE_LABEL_SIZE: f32 = 32.0;
+const FACE_LABEL_DISTANCE: f32 = 0.65; // From the cube's center in cube units, just off the face
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Game), hud_setup);
+}
+
+fn hud_setup(mut commands: Commands, font_family: Res<MainFont>, preferences: Res<Preferences>) {
+    commands.spawn((
+        DespawnOnExit(GameState::Game),
+        Hud,
+        Text::new(controls_hint(&preferences)),
+        TextFont {
+            font_size: 16.0,
+            font: font_family.0.clone(),
+            ..default()
+        },
+        TextColor(HUD_TEXT_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            left: px(12),
+            bottom: px(12),
+            ..default()
+        },
+        hud_visibility(&preferences),
+        Pickable::IGNORE,
+    ));
+
+    commands.spawn((
+        DespawnOnExit(GameState::Game),
+        Hud,
+        Node {
+            position_type: PositionType::Absolute,
+            right: px(12),
+            top: px(12),
+            ..default()
+        },
+        hud_visibility(&preferences),
+        Pickable::IGNORE,
+        children![(
+            MoveLockIndicator,
+            Text::default(),
+            TextFont {
+                font_size: 16.0,
+                font: font_family.0.clone(),
+                ..default()
+            },
+            TextColor(HUD_TEXT_COLOR),
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        )],
+    ));
+
+    // Shown even in zen mode, it only appears in response to a key press
+    commands.spawn((
+        DespawnOnExit(GameState::Game),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            top: px(24),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![(
+            Toast(Timer::from_seconds(TOAST_SECS, TimerMode::Once)),
+            Text::default(),
+            TextFont {
+                font_size: 24.0,
+                font: font_family.0.clone(),
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        )],
+    ));
+
+    let label_font = TextFont {
+        font_size: 22.0,
+        font: font_family.0.clone(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            DespawnOnExit(GameState::Game),
+            Hud,
+            Node {
+                position_type: PositionType::Absolute,
+                width: percent(100),
+                height: percent(100),
+                ..default()
+            },
+            hud_visibility(&preferences),
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            for face in CubeFace::ALL {
+                parent.spawn((
+                    FaceLabel(face),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        width: px(FACE_LABEL_SIZE),
+                        height: px(FACE_LABEL_SIZE),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(FACE_LABEL_BACKGROUND_COLOR),
+                    Visibility::Hidden,
+                    Pickable::IGNORE,
+                    children![(Text::default(), label_font.clone(), TextColor::WHITE)],
+                ));
+            }
+        });
+}
+
+fn controls_hint(preferences: &Preferences) -> String {
+    let mut controls = vec!["Left drag: turn a layer"];
+    if preferences.scroll_turns && preferences.control_scheme == ControlScheme::Drag {
+        controls.push("Scroll: turn the hovered face");
+    }
+    if preferences.free_rotation {
+        controls.extend(["Right drag: rotate the cube", "1-3: views"]);
+    }
+    controls.extend([
+        "E: exploded view",
+        "C: recenter",
+        "X: x-ray",
+        "P: color scheme",
+        "Z: zen mode",
+    ]);
+
+    controls.join("   ")
+}
+
+pub(super) fn face_labels_system(
+    preferences: Res<Preferences>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube_transform: Single<&GlobalTransform, With<Cube>>,
+    mut labels: Query<(&FaceLabel, &mut Node, &mut Visibility, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let (camera, camera_transform) = *camera;
+
+    for (label, mut node, mut visibility, children) in &mut labels {
+        let normal = cube_transform.rotation() * label.0.normal();
+        let center = cube_transform.transform_point(label.0.normal() * FACE_LABEL_DISTANCE);
+
+        let facing_camera = (camera_transform.translation() - center).dot(normal) > 0.0;
+        let viewport_position = camera.world_to_viewport(camera_transform, center);
+
+        let Some(viewport_position) = viewport_position
+            .ok()
+            .filter(|_| preferences.show_face_labels && facing_camera)
+        else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                let letter = face_letter(camera_transform.rotation().inverse() * normal);
+                if text.0 != letter {
+                    text.0 = letter.to_string();
+                }
+            }
+        }
+
+        node.left = px(viewport_position.x - FACE_LABEL_SIZE / 2.0);
+        node.top = px(viewport_position.y - FACE_LABEL_SIZE / 2.0);
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+// Standard notation names faces by where they point relative to the viewer, not by their color
+fn face_letter(view_space_normal: Vec3) -> &'static str {
+    match CubeFace::from_normal(view_space_normal) {
+        CubeFace::PosX => "R",
+        CubeFace::NegX => "L",
+        CubeFace::PosY => "U",
+        CubeFace::NegY => "D",
+        CubeFace::PosZ => "F",
+        CubeFace::NegZ => "B",
+    }
+}
+
+fn hud_visibility(preferences: &Preferences) -> Visibility {
+    if preferences.zen_mode {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+pub(super) fn toggle_zen_mode_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut preferences: ResMut<Preferences>,
+) {
+    if keys.just_pressed(TOGGLE_ZEN_MODE_KEY) {
+        preferences.zen_mode = !preferences.zen_mode;
+    }
+}
+
+pub(super) fn apply_zen_mode_system(
+    preferences: Res<Preferences>,
+    mut hud: Query<&mut Visibility, With<Hud>>,
+) {
+    for mut visibility in &mut hud {
+        visibility.set_if_neq(hud_visibility(&preferences));
+    }
+}
+
+pub(super) fn show_toast(
+    toast: Single<(&mut Toast, &mut Text, &mut Visibility)>,
+    message: impl Into<String>,
+) {
+    let (mut toast, mut text, mut visibility) = toast.into_inner();
+    toast.0.reset();
+    text.0 = message.into();
+    *visibility = Visibility::Inherited;
+}
+
+pub(super) fn toast_system(time: Res<Time>, toast: Single<(&mut Toast, &mut Visibility)>) {
+    let (mut toast, mut visibility) = toast.into_inner();
+    if toast.0.tick(time.delta()).just_finished() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+pub(super) fn wide_turn_toast_system(
+    wide_turns: Query<(), (With<Cube>, Added<WideTurn>)>,
+    toast: Single<(&mut Toast, &mut Text, &mut Visibility)>,
+) {
+    if !wide_turns.is_empty() {
+        show_toast(toast, "Wide turn");
+    }
+}
+
+pub(super) fn move_lock_indicator_system(
+    time: Res<Time>,
+    preferences: Res<Preferences>,
+    last_move_completion: Res<LastMoveCompletion>,
+    exploded_view: Res<ExplodedView>,
+    cube: Single<(Has<ActiveCubeRotation>, Has<PendingTurn>), With<Cube>>,
+    indicator: Single<(&mut Text, &mut Visibility), With<MoveLockIndicator>>,
+) {
+    let (mut text, mut visibility) = indicator.into_inner();
+
+    let (turning, awaiting_confirmation) = *cube;
+
+    let reason = if awaiting_confirmation {
+        Some("Click to make the turn, Esc to cancel")
+    } else if exploded_view.blocks_moves() {
+        Some("Moves locked: exploded view")
+    } else if turning {
+        Some("Moves locked: turning")
+    } else if move_on_cooldown(&time, &preferences, &last_move_completion) {
+        Some("Moves locked: cooldown")
+    } else {
+        None
+    };
+
+    let Some(reason) = reason else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    if text.0 != reason {
+        text.0 = reason.to_string();
+    }
+    visibility.set_if_neq(Visibility::Inherited);
+}
@@ -0,0 +1,241 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    GameState,
+    preferences::{ControlScheme, Preferences},
+};
+
+use super::{
+    ActiveCubeRotation, Cube, CubeFace, Cubie, CubieBounds, IdleCube, LastMoveCompletion,
+    ROTATION_SPEED, cursor_pick, exploded::ExplodedView, grab_layers, move_on_cooldown,
+    pick_layer_candidates, picking::CubiePick, target_layer, turn_overshoot, turned_layers,
+};
+
+// Sticker picked in the select-and-turn control scheme, kept until the next click
+#[derive(Resource, Default)]
+pub(super) struct SelectedLayer(pub(super) Option<CubiePick>);
+
+const SELECTION_COLOR: Color = Color::srgb(1.0, 0.2, 0.8);
+const SELECTION_OFFSET: f32 = 0.01; // Lifts the outline off the face to avoid z-fighting
+
+// Viewport y grows downwards
+const TURN_KEYS: [(KeyCode, Vec2); 4] = [
+    (KeyCode::ArrowUp, Vec2::NEG_Y),
+    (KeyCode::ArrowDown, Vec2::Y),
+    (KeyCode::ArrowLeft, Vec2::NEG_X),
+    (KeyCode::ArrowRight, Vec2::X),
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SelectedLayer>()
+        .add_systems(OnExit(GameState::Game), clear_selection);
+}
+
+pub(super) fn select_layer_system(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube_transform: Single<&GlobalTransform, With<Cube>>,
+    cubies: Query<(&Cubie, &Transform, &CubieBounds)>,
+    preferences: Res<Preferences>,
+    mut selected_layer: ResMut<SelectedLayer>,
+) {
+    if preferences.control_scheme != ControlScheme::SelectAndTurn {
+        return;
+    }
+
+    if !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    let (camera, camera_transform) = camera.into_inner();
+
+    // Clicking outside the cube clears the selection
+    selected_layer.0 = cursor_pick(
+        cursor_position,
+        camera,
+        camera_transform,
+        *cube_transform,
+        cubies,
+    )
+    .ok();
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn turn_selected_layer_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube: Single<(Entity, &GlobalTransform), IdleCube>,
+    cubies: Query<(Entity, &Cubie, &Transform)>,
+    selected_layer: Res<SelectedLayer>,
+    time: Res<Time>,
+    preferences: Res<Preferences>,
+    last_move_completion: Res<LastMoveCompletion>,
+    exploded_view: Res<ExplodedView>,
+) {
+    if preferences.control_scheme != ControlScheme::SelectAndTurn || exploded_view.blocks_moves() {
+        return;
+    }
+
+    let Some(pick) = &selected_layer.0 else {
+        return;
+    };
+
+    let Some(turn_direction) = TURN_KEYS
+        .iter()
+        .find(|(key, _)| keys.just_pressed(*key))
+        .map(|(_, direction)| *direction)
+    else {
+        return;
+    };
+
+    if move_on_cooldown(&time, &preferences, &last_move_completion) {
+        return;
+    }
+
+    let (camera, camera_transform) = camera.into_inner();
+
+    // Directions are recomputed on every press so they follow the cube after it's been rotated
+    let Ok(candidates) = pick_layer_candidates(pick, camera, camera_transform, cube.1) else {
+        return;
+    };
+
+    let Some((axis, index, viewport_dir)) = candidates.into_iter().max_by(|a, b| {
+        a.2.dot(turn_direction)
+            .abs()
+            .total_cmp(&b.2.dot(turn_direction).abs())
+    }) else {
+        return;
+    };
+
+    let layers = turned_layers(
+        target_layer(index, &keys, &preferences),
+        &keys,
+        &preferences,
+    );
+    grab_layers(&mut commands, cube.0, &cubies, axis, layers);
+
+    let target_rotations = if viewport_dir.dot(turn_direction) > 0.0 {
+        1
+    } else {
+        3
+    };
+    commands.entity(cube.0).insert(ActiveCubeRotation {
+        axis,
+        current_angle: 0.0,
+        target_rotations,
+        speed: ROTATION_SPEED,
+        overshoot: turn_overshoot(&preferences, 0.0, target_rotations),
+    });
+}
+
+pub(super) fn draw_selected_layer_system(
+    cube_transform: Single<&GlobalTransform, With<Cube>>,
+    cubies: Query<(&Cubie, &Transform, &CubieBounds)>,
+    selected_layer: Res<SelectedLayer>,
+    preferences: Res<Preferences>,
+    mut gizmos: Gizmos,
+) {
+    if preferences.control_scheme != ControlScheme::SelectAndTurn {
+        return;
+    }
+
+    let Some(pick) = &selected_layer.0 else {
+        return;
+    };
+
+    // Outline whichever cubie currently sits in the selected slot
+    let Some((_, cubie_transform, bounds)) = cubies
+        .iter()
+        .find(|(cubie, _, _)| cubie.position == pick.position)
+    else {
+        return;
+    };
+
+    let (min, max) = (0..8)
+        .map(|corner| {
+            cubie_transform.transform_point(Vec3::select(
+                BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                bounds.max,
+                bounds.min,
+            ))
+        })
+        .fold((Vec3::MAX, Vec3::MIN), |(min, max), point| {
+            (min.min(point), max.max(point))
+        });
+
+    let normal = pick.face.normal();
+    let (u, v) = match pick.face {
+        CubeFace::PosX | CubeFace::NegX => (Vec3::Y, Vec3::Z),
+        CubeFace::PosY | CubeFace::NegY => (Vec3::Z, Vec3::X),
+        CubeFace::PosZ | CubeFace::NegZ => (Vec3::X, Vec3::Y),
+    };
+
+    let half_extents = (max - min) / 2.0;
+    let center = (min + max) / 2.0 + normal * (half_extents + SELECTION_OFFSET);
+    let (u, v) = (u * half_extents, v * half_extents);
+
+    gizmos.linestrip(
+        [
+            center - u - v,
+            center + u - v,
+            center + u + v,
+            center - u + v,
+            center - u - v,
+        ]
+        .map(|point| cube_transform.transform_point(point)),
+        SELECTION_COLOR,
+    );
+}
+
+fn clear_selection(mut selected_layer: ResMut<SelectedLayer>) {
+    selected_layer.0 = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::game::{
+        BeingDragged, CubeAxis, layer_index,
+        test_support::{cube_entity, move_input_world},
+    };
+
+    // Turn the arrow key starts on the front center sticker, as (axis, layer, target rotations)
+    fn arrow_turn(key: KeyCode) -> (CubeAxis, u32, u32) {
+        let mut world = move_input_world(ControlScheme::SelectAndTurn);
+        world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        world.run_system_once(turn_selected_layer_system).unwrap();
+
+        let cube = cube_entity(&mut world);
+        let rotation = world.get::<ActiveCubeRotation>(cube).unwrap();
+        let (axis, target_rotations) = (rotation.axis, rotation.target_rotations);
+        let layer = world
+            .query_filtered::<&Cubie, With<BeingDragged>>()
+            .iter(&world)
+            .map(|cubie| layer_index(cubie.position, axis))
+            .next()
+            .unwrap();
+        (axis, layer, target_rotations)
+    }
+
+    #[test]
+    fn arrow_keys_turn_the_selected_stickers_row_or_column() {
+        let (axis, layer, right) = arrow_turn(KeyCode::ArrowRight);
+        assert_eq!((axis, layer), (CubeAxis::Y, 1));
+        let (axis, layer, left) = arrow_turn(KeyCode::ArrowLeft);
+        assert_eq!((axis, layer), (CubeAxis::Y, 1));
+        assert_eq!((left + right) % 4, 0, "left and right turn the same way");
+
+        let (axis, layer, up) = arrow_turn(KeyCode::ArrowUp);
+        assert_eq!((axis, layer), (CubeAxis::X, 1));
+        let (_, _, down) = arrow_turn(KeyCode::ArrowDown);
+        assert_eq!((up + down) % 4, 0, "up and down turn the same way");
+    }
+}
@@ -1,27 +1,80 @@
-use std::f32::consts::PI;
+#[cfg(feature = "bench")]
+pub mod bench_support;
+mod color_schemes;
+mod cube_state;
+mod cursor;
+mod debug;
+mod exploded;
+mod ghost_preview;
+mod hud;
+mod layer_select;
+mod moves;
+mod picking;
+#[cfg(any(test, feature = "bench"))]
+mod test_support;
+mod turn_hints;
+mod view_presets;
+
+use std::{collections::VecDeque, f32::consts::PI, ops::RangeInclusive};
 
 use super::*;
 
+use crate::preferences::{
+    CameraView, ColorScheme, ControlScheme, CubeRotationMode, CubeShape, CubeStyle, Handedness,
+    Preferences, StickerCalibration, StickerColor, StickerStyle, TurnEasing,
+};
+
+pub use cube_state::CubeState;
+pub use moves::{
+    Move, MoveCallbacks, MoveError, SliceConvention, legal_moves, legal_moves_after, try_apply_move,
+};
+
+use picking::{CubiePick, pick_cubie};
+
 use bevy::{
-    asset::RenderAssetUsages,
-    image::{ImageAddressMode, ImageFilterMode, ImageLoaderSettings},
-    input::mouse::MouseMotion,
+    asset::{AssetLoadFailedEvent, RenderAssetUsages},
+    image::{
+        ImageAddressMode, ImageFilterMode, ImageLoaderSettings, ImageSampler,
+        ImageSamplerDescriptor,
+    },
+    input::mouse::{MouseMotion, MouseWheel},
     mesh::{Indices, PrimitiveTopology},
     prelude::*,
+    render::render_resource::{Extent3d, Face, TextureDimension, TextureFormat},
+    window::PrimaryWindow,
 };
 
 #[derive(Component)]
 struct Cube;
 
-#[derive(Debug, Clone, Copy)]
-enum CubeAxis {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeAxis {
     X,
     Y,
     Z,
 }
 
-#[derive(Debug, Clone, Copy)]
-enum CubeFace {
+impl CubeAxis {
+    fn unit(self) -> Vec3 {
+        match self {
+            CubeAxis::X => Vec3::X,
+            CubeAxis::Y => Vec3::Y,
+            CubeAxis::Z => Vec3::Z,
+        }
+    }
+}
+
+/// Notable things the player did with the cube, sent as messages.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeEvent {
+    /// A layer drag ended without a turn, the press never became a drag or the layer was let go
+    /// short of a quarter turn.
+    DragAborted,
+}
+
+/// A face of the cube, named after the axis it points along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
     PosX,
     NegX,
     PosY,
@@ -30,6 +83,49 @@ enum CubeFace {
     NegZ,
 }
 
+impl CubeFace {
+    const ALL: [CubeFace; 6] = [
+        CubeFace::PosX,
+        CubeFace::NegX,
+        CubeFace::PosY,
+        CubeFace::NegY,
+        CubeFace::PosZ,
+        CubeFace::NegZ,
+    ];
+
+    fn from_normal(normal: Vec3) -> Self {
+        let abs = normal.abs();
+        if abs.x >= abs.y && abs.x >= abs.z {
+            if normal.x > 0.0 {
+                CubeFace::PosX
+            } else {
+                CubeFace::NegX
+            }
+        } else if abs.y >= abs.z {
+            if normal.y > 0.0 {
+                CubeFace::PosY
+            } else {
+                CubeFace::NegY
+            }
+        } else if normal.z > 0.0 {
+            CubeFace::PosZ
+        } else {
+            CubeFace::NegZ
+        }
+    }
+
+    fn normal(self) -> Vec3 {
+        match self {
+            CubeFace::PosX => Vec3::X,
+            CubeFace::NegX => -Vec3::X,
+            CubeFace::PosY => Vec3::Y,
+            CubeFace::NegY => -Vec3::Y,
+            CubeFace::PosZ => Vec3::Z,
+            CubeFace::NegZ => -Vec3::Z,
+        }
+    }
+}
+
 struct CubeFaceColors {
     pos_x: Option<Color>,
     neg_x: Option<Color>,
@@ -39,6 +135,15 @@ struct CubeFaceColors {
     neg_z: Option<Color>,
 }
 
+// Press on a face drag assist is turning towards the camera. The drag starts once the cube has
+// settled, so its layer and direction come from where the face ends up.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct DeferredDrag {
+    pick: CubiePick,
+    viewport_origin: Vec2,
+}
+
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 struct PendingDrag {
@@ -58,6 +163,7 @@ struct ActiveDrag {
     viewport_origin: Vec2,
     viewport_dir: Vec2,
     current_angle: f32,
+    angular_velocity: f32, // Smoothed, radians per second
 }
 
 #[derive(Component)]
@@ -66,8 +172,48 @@ struct ActiveCubeRotation {
     axis: CubeAxis,
     current_angle: f32,
     target_rotations: u32,
+    speed: f32, // Radians per second, starts above ROTATION_SPEED when a layer is flicked
+    overshoot: f32, // How far past the target the layer swings before settling, zero once it has
+}
+
+// Dragged layer let go with turn confirmation on, held where it was released until the turn is
+// confirmed or cancelled
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct PendingTurn {
+    axis: CubeAxis,
+    current_angle: f32,
+    target_rotations: u32,
 }
 
+// The turn under way moves the middle layer along with the outer one
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct WideTurn;
+
+// A cube with no layer move in progress. Every system that starts a move requires it, and as they're
+// chained their commands apply in between, so a drag and a key press in the same frame can't both
+// start one.
+type IdleCube = (
+    With<Cube>,
+    Without<ActiveDrag>,
+    Without<DeferredDrag>,
+    Without<PendingDrag>,
+    Without<PendingTurn>,
+    Without<ActiveCubeRotation>,
+);
+
+// Brief bump of the cube's scale when a dragged layer passes a quarter turn, like a real cube's
+// detents
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct DetentPulse(Timer);
+
+// Cube root on its way back to the origin, its rotation is left alone
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+struct Recentering;
+
 #[derive(Component)]
 #[component(storage = "SparseSet")]
 struct BeingDragged {
@@ -77,35 +223,207 @@ struct BeingDragged {
 #[derive(Component)]
 struct Cubie {
     position: (u32, u32, u32), // (0, 0, 0) is left-bottom-back, (2, 2, 2) is right-top-front
+    home_position: (u32, u32, u32), // Where the cubie sits on a solved cube
+}
+
+// Inverted hull drawn around a cubie, only its inside faces show so it reads as an outline
+#[derive(Component)]
+struct CubieOutline;
+
+#[derive(Resource)]
+struct CubieMaterial {
+    material: Handle<StandardMaterial>,
+    border_texture: Handle<Image>,
+    custom_border_texture: Option<Handle<Image>>, // Generated when the style asks for a border width
+    outline_material: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Default)]
+struct LastMoveCompletion(Option<f32>); // Elapsed seconds when the last layer turn settled
+
+// Scroll turns that came in while a layer was still turning, started in order as each one settles
+#[derive(Resource, Default)]
+struct QueuedTurns(VecDeque<QueuedTurn>);
+
+#[derive(Clone, Debug, PartialEq)]
+struct QueuedTurn {
+    axis: CubeAxis,
+    layers: RangeInclusive<u32>,
+    target_rotations: u32,
+}
+
+// Extents of a cubie's box in its own (unscaled) local space
+#[derive(Component)]
+struct CubieBounds {
+    min: Vec3,
+    max: Vec3,
+}
+
+// Why a click couldn't be turned into a layer drag
+#[derive(Debug, Clone, Copy)]
+enum PickFailure {
+    OnCooldown,
+    NoCursor,
+    RayCast,
+    Missed,
+    Projection,
+    EdgeOn,
+}
+
+impl std::fmt::Display for PickFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PickFailure::OnCooldown => "the previous move's cooldown hasn't passed",
+            PickFailure::NoCursor => "the cursor is outside the window",
+            PickFailure::RayCast => "the camera couldn't cast a ray through the cursor",
+            PickFailure::Missed => "the ray missed every cubie",
+            PickFailure::Projection => "the picked face couldn't be projected to the viewport",
+            PickFailure::EdgeOn => {
+                "the picked face is seen edge-on, so drag directions are degenerate"
+            }
+        })
+    }
 }
 
 const CLEAR_COLOR: Color = Color::srgb(0.40, 0.36, 0.23);
+const CUBIE_BASE_COLOR: Color = Color::srgb(0.0, 0.0, 0.0); // Interior faces and stickerless plastic body
+
+const X_RAY_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35); // Multiplies the sticker colors
+
+const MIRROR_COLOR: Color = Color::srgb(0.78, 0.78, 0.80);
+
+const OUTLINE_COLOR: Color = Color::srgb(0.08, 0.07, 0.05);
+const OUTLINE_SCALE: f32 = 1.06; // Outline size relative to its cubie
+
+const DRAG_ASSIST_FACING: f32 = 0.5; // Cosine of the angle between a face and the camera the assist aims for
+
+// Thickness of the outer layers of the mirror cube along each axis. The inner cuts stay where the
+// regular cube has them, so turning works exactly the same; only the cubie shapes change.
+const MIRROR_NEG_LAYER_THICKNESS: Vec3 = Vec3::new(0.22, 0.42, 0.28);
+const MIRROR_POS_LAYER_THICKNESS: Vec3 = Vec3::new(0.44, 0.25, 0.39);
+
+const CAMERA_DISTANCE: f32 = 3.0;
+
+const ROTATION_SPEED: f32 = PI; // Radians per second when settling a layer
+const TURN_OVERSHOOT: f32 = PI / 24.0; // Furthest a bouncing turn swings past its quarter turn
+const MAX_QUEUED_TURNS: usize = 3; // Scroll notches kept while a layer turns, later ones are dropped
+const MAX_ROTATION_STEP_SECS: f32 = 1.0 / 30.0; // Longer frames (lag spikes) are animated as this long
+
+// Layer momentum, a released layer keeps going as if it were still dragged for this long
+const MOMENTUM_SECS: f32 = 0.12;
+const MOMENTUM_DECAY: f32 = 6.0; // Per second, how fast a flicked layer slows down to ROTATION_SPEED
+const DETENT_PULSE_SECS: f32 = 0.12;
+const DETENT_PULSE_SCALE: f32 = 0.025; // Extra scale at the start of the pulse
+
+const RECENTER_SPEED: f32 = 4.0; // Cube units per second
+
+const DRAG_VELOCITY_SMOOTHING: f32 = 20.0; // Per second, higher follows the cursor more closely
+
+// Layout of the face texture, the bordered sticker on the left half and a flat face on the right
+const FACE_TEXTURE_SIZE: u32 = 128;
+const FLAT_FACE_SHADE: u8 = 244;
+const BORDER_CORNER_ROUNDNESS: f32 = 2.0; // Sticker corner radius relative to the border width
+const BUNDLED_BORDER_WIDTH: f32 = 0.04; // Closest generated match to cubie_face.png
+
+const TOGGLE_STICKERS_KEY: KeyCode = KeyCode::KeyT;
+const TOGGLE_X_RAY_KEY: KeyCode = KeyCode::KeyX;
+const RECENTER_KEY: KeyCode = KeyCode::KeyC;
+const CANCEL_TURN_KEY: KeyCode = KeyCode::Escape;
+const OPPOSITE_LAYER_KEYS: [KeyCode; 2] = [KeyCode::AltLeft, KeyCode::AltRight];
+const WIDE_TURN_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
 
 pub(crate) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Game), game_setup)
-        .add_systems(
-            Update,
+    app.add_plugins((
+        color_schemes::plugin,
+        cursor::plugin,
+        debug::plugin,
+        exploded::plugin,
+        ghost_preview::plugin,
+        hud::plugin,
+        layer_select::plugin,
+    ))
+    .init_resource::<LastMoveCompletion>()
+    .init_resource::<QueuedTurns>()
+    .add_message::<CubeEvent>()
+    .init_resource::<MoveCallbacks>()
+    .add_systems(OnEnter(GameState::Game), game_setup)
+    .add_systems(
+        Update,
+        (
             (
                 cube_rotation_system,
-                (
-                    cubie_drag_init_system,
-                    cubie_drag_pending_system,
-                    cubie_drag_system,
-                    cubie_rotation_system,
-                )
-                    .chain(),
+                lock_cube_rotation_system.run_if(resource_changed::<Preferences>),
+            )
+                .chain(),
+            (start_recenter_system, recenter_system).chain(),
+            (
+                view_presets::view_preset_keys_system,
+                view_presets::auto_align_system,
+                view_presets::snap_to_view_system,
+            )
+                .chain(),
+            (
+                confirm_turn_system,
+                cubie_drag_init_system,
+                deferred_drag_system,
+                layer_select::select_layer_system,
+                layer_select::turn_selected_layer_system,
+                scroll_turn_system,
+                cubie_drag_pending_system,
+                cubie_drag_system,
+                cubie_rotation_system,
+                queued_turn_system,
+                ghost_preview::ghost_preview_system,
             )
-                .run_if(in_state(GameState::Game)),
+                .chain(),
+            detent_pulse_system,
+            cursor::hide_cursor_while_dragging_system,
+            (
+                exploded::toggle_exploded_view_system,
+                exploded::exploded_view_system,
+            )
+                .chain(),
+            (
+                toggle_sticker_style_system,
+                toggle_x_ray_system,
+                apply_cube_style_system.run_if(resource_changed::<Preferences>),
+                border_texture_fallback_system,
+            )
+                .chain(),
+            (
+                debug::toggle_debug_options_system,
+                debug::draw_cube_axes_system,
+                debug::cubie_tooltip_system,
+                debug::cubie_index_labels_system,
+            )
+                .chain(),
+            layer_select::draw_selected_layer_system,
+            turn_hints::draw_turn_hints_system,
+            hud::face_labels_system,
+            hud::toast_system,
+            hud::move_lock_indicator_system,
+            hud::wide_turn_toast_system,
+            color_schemes::cycle_color_scheme_system,
+            (
+                hud::toggle_zen_mode_system,
+                hud::apply_zen_mode_system.run_if(resource_changed::<Preferences>),
+            )
+                .chain(),
         )
-        .add_systems(OnExit(GameState::Game), game_cleanup);
+            .run_if(in_state(GameState::Game)),
+    )
+    .add_systems(OnExit(GameState::Game), game_cleanup);
 }
 
 fn cube_rotation_system(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     mut motion_events: MessageReader<MouseMotion>,
-    mut cube_transform: Single<&mut Transform, With<Cube>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_transform: Single<&GlobalTransform, With<Camera>>,
+    cube: Single<(&mut Transform, Has<view_presets::SnappingToView>), With<Cube>>,
+    preferences: Res<Preferences>,
 ) {
-    if !mouse_buttons.pressed(MouseButton::Right) {
+    if !preferences.free_rotation || !mouse_buttons.pressed(MouseButton::Right) {
         return;
     }
 
@@ -114,194 +432,635 @@ fn cube_rotation_system(
         delta += event.delta;
     }
 
-    if delta == Vec2::ZERO {
+    // A view preset takes over until the cube reaches it, the motion in between is dropped
+    let (mut cube_transform, snapping_to_view) = cube.into_inner();
+    if delta == Vec2::ZERO || snapping_to_view {
         return;
     }
 
-    let yaw = Quat::from_rotation_y(delta.x * 0.01);
-    let pitch = Quat::from_rotation_x(delta.y * 0.01);
+    if preferences.invert_rotation_y {
+        delta.y = -delta.y;
+    }
+
+    let rotation = match preferences.cube_rotation_mode {
+        CubeRotationMode::Turntable => {
+            let yaw = Quat::from_rotation_y(delta.x * 0.01);
+            // Pitches around the screen's horizontal axis whichever way the camera looks
+            let pitch = Quat::from_axis_angle(camera_transform.right().as_vec3(), delta.y * 0.01);
+
+            // Yaw never changes the tilt, so it's applied right away and only the pitch is limited
+            cube_transform.rotation = apply_free_rotation(cube_transform.rotation, yaw, None);
+            pitch
+        }
+        CubeRotationMode::Trackball => {
+            let Some(cursor_position) = window.cursor_position() else {
+                return;
+            };
+
+            let window_size = window.size();
+            let from = trackball_point(cursor_position - delta, window_size);
+            let to = trackball_point(cursor_position, window_size);
+
+            // The arc is in view space, bring it into world space
+            let camera_rotation = camera_transform.rotation();
+            camera_rotation * Quat::from_rotation_arc(from, to) * camera_rotation.inverse()
+        }
+    };
+
+    cube_transform.rotation = apply_free_rotation(
+        cube_transform.rotation,
+        rotation,
+        preferences.max_cube_pitch_degrees,
+    );
+}
+
+// Both views keep the camera the same distance from the cube, so it looks the same size
+fn camera_view_transform(view: CameraView) -> Transform {
+    match view {
+        CameraView::HeadOn => Transform::from_xyz(0.0, 0.0, CAMERA_DISTANCE),
+        CameraView::Corner => {
+            Transform::from_translation(Vec3::new(-1.0, 1.0, 1.0).normalize() * CAMERA_DISTANCE)
+                .looking_at(Vec3::ZERO, Vec3::Y)
+        }
+    }
+}
+
+// Three quarter view with the top, front and right faces showing. The corner camera already looks
+// at the cube from above a corner, so there it's left square instead of angling it a second time.
+fn default_cube_rotation(view: CameraView) -> Quat {
+    match view {
+        CameraView::HeadOn => Quat::from_euler(
+            EulerRot::XYZ,
+            /*PI / 6.0*/ 30.0_f32.to_radians(),
+            -PI / 4.0,
+            0.0,
+        ),
+        CameraView::Corner => Quat::IDENTITY,
+    }
+}
+
+// With free rotation off the cube is held still at its starting orientation
+fn lock_cube_rotation_system(
+    preferences: Res<Preferences>,
+    mut cube_transform: Single<&mut Transform, With<Cube>>,
+) {
+    if !preferences.free_rotation {
+        cube_transform.rotation = default_cube_rotation(preferences.camera_view);
+    }
+}
+
+fn start_recenter_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    cube: Single<(Entity, &Transform), With<Cube>>,
+) {
+    if keys.just_pressed(RECENTER_KEY) && cube.1.translation != Vec3::ZERO {
+        commands.entity(cube.0).insert(Recentering);
+    }
+}
+
+// Only moves the cube root, so layer turns and free rotation carry on undisturbed
+#[allow(clippy::type_complexity)]
+fn recenter_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    cube: Single<(Entity, &mut Transform), (With<Cube>, With<Recentering>)>,
+) {
+    let (cube_entity, mut cube_transform) = cube.into_inner();
+
+    cube_transform.translation = cube_transform
+        .translation
+        .move_towards(Vec3::ZERO, RECENTER_SPEED * time.delta_secs());
+
+    if cube_transform.translation == Vec3::ZERO {
+        commands.entity(cube_entity).remove::<Recentering>();
+    }
+}
+
+// Applies a world-space rotation to the cube unless it tilts it past `max_pitch_degrees`. Rotating
+// back towards upright is always allowed so the cube can't get stuck past the limit. The result is
+// renormalized, otherwise the rounding error of many small steps piles up over a long session and
+// the quaternion starts scaling and skewing the cube.
+fn apply_free_rotation(
+    cube_rotation: Quat,
+    rotation: Quat,
+    max_pitch_degrees: Option<f32>,
+) -> Quat {
+    let rotated = (rotation * cube_rotation).normalize();
+    if max_pitch_degrees.is_none_or(|max_pitch| {
+        let tilt = cube_tilt(rotated);
+        tilt <= max_pitch.to_radians() || tilt <= cube_tilt(cube_rotation)
+    }) {
+        rotated
+    } else {
+        cube_rotation
+    }
+}
+
+// Maps a viewport position onto a unit sphere filling the window, in view space. Far from the
+// center the sphere blends into a hyperbolic sheet, so dragging past its edge still rolls smoothly.
+fn trackball_point(viewport_position: Vec2, window_size: Vec2) -> Vec3 {
+    let radius = window_size.min_element() / 2.0;
+    let offset = (viewport_position - window_size / 2.0) / radius;
+    let point = Vec2::new(offset.x, -offset.y); // Viewport y grows downwards
+
+    let distance_squared = point.length_squared();
+    let z = if distance_squared <= 0.5 {
+        (1.0 - distance_squared).sqrt()
+    } else {
+        0.5 / distance_squared.sqrt()
+    };
 
-    cube_transform.rotate(yaw);
-    cube_transform.rotate(pitch);
+    point.extend(z).normalize()
 }
 
+// Angle between the cube's up axis and the world's
+fn cube_tilt(rotation: Quat) -> f32 {
+    (rotation * Vec3::Y).angle_between(Vec3::Y)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn cubie_drag_init_system(
     mut commands: Commands,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
-    window: Single<&Window>,
+    window: Single<&Window, With<PrimaryWindow>>,
     camera: Single<(&Camera, &GlobalTransform)>,
-    cube: Single<
-        (Entity, &GlobalTransform),
-        (
-            With<Cube>,
-            Without<ActiveDrag>,
-            Without<PendingDrag>,
-            Without<ActiveCubeRotation>,
-        ),
-    >,
+    cube: Single<(Entity, &GlobalTransform, Has<view_presets::AutoAligning>), IdleCube>,
+    cubies: Query<(&Cubie, &Transform, &CubieBounds)>,
+    time: Res<Time>,
+    preferences: Res<Preferences>,
+    last_move_completion: Res<LastMoveCompletion>,
+    exploded_view: Res<exploded::ExplodedView>,
+    debug_options: Res<debug::DebugOptions>,
 ) {
+    if preferences.control_scheme != ControlScheme::Drag || exploded_view.blocks_moves() {
+        return;
+    }
+
     if !mouse_buttons.just_pressed(MouseButton::Left) {
         return;
     }
 
-    let (camera, global_transform) = camera.into_inner();
+    if cube.2 {
+        commands
+            .entity(cube.0)
+            .remove::<(view_presets::SnappingToView, view_presets::AutoAligning)>();
+    }
+
+    if move_on_cooldown(&time, &preferences, &last_move_completion) {
+        debug::log_pick_failure(&debug_options, PickFailure::OnCooldown);
+        return;
+    }
+
+    let (camera, camera_transform) = camera.into_inner();
     let Some(cursor_position) = window.cursor_position() else {
+        debug::log_pick_failure(&debug_options, PickFailure::NoCursor);
         return;
     };
 
-    let Ok(ray) = camera.viewport_to_world(global_transform, cursor_position) else {
-        return;
+    let pick = match cursor_pick(cursor_position, camera, camera_transform, cube.1, cubies) {
+        Ok(pick) => pick,
+        Err(failure) => {
+            debug::log_pick_failure(&debug_options, failure);
+            return;
+        }
     };
 
-    let inv = cube.1.affine().inverse();
+    // Tilted even when the face is too edge-on to drag, the press waits for it to settle
+    if let Some(target) = drag_assist_rotation(&pick, camera_transform, cube.1)
+        .filter(|_| preferences.drag_assist && preferences.free_rotation)
+    {
+        commands.entity(cube.0).insert((
+            view_presets::SnappingToView { target },
+            view_presets::AutoAligning,
+            DeferredDrag {
+                pick,
+                viewport_origin: cursor_position,
+            },
+        ));
+        return;
+    }
 
-    let local_origin = inv.transform_point3(ray.origin);
-    let local_dir = inv.transform_vector3(ray.direction.as_vec3()).normalize();
+    match pending_drag(&pick, cursor_position, camera, camera_transform, cube.1) {
+        Ok(pending_drag) => {
+            commands.entity(cube.0).insert(pending_drag);
+        }
+        Err(failure) => debug::log_pick_failure(&debug_options, failure),
+    }
+}
 
-    let local_ray = Ray3d {
-        origin: local_origin,
-        direction: Dir3::new(local_dir).expect("Direction should be normalized"),
-    };
+fn deferred_drag_system(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube: Single<
+        (
+            Entity,
+            &Transform,
+            &DeferredDrag,
+            Has<view_presets::SnappingToView>,
+        ),
+        With<Cube>,
+    >,
+    debug_options: Res<debug::DebugOptions>,
+    mut cube_events: MessageWriter<CubeEvent>,
+) {
+    let (cube_entity, cube_transform, deferred_drag, snapping) = *cube;
 
-    let min = Vec3::splat(-0.5);
-    let max = Vec3::splat(0.5);
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        commands.entity(cube_entity).remove::<DeferredDrag>();
+        cube_events.write(CubeEvent::DragAborted);
+        return;
+    }
 
-    let inv_dir = 1.0 / local_ray.direction.as_vec3();
+    if snapping {
+        return;
+    }
 
-    let t1 = (min - local_ray.origin) * inv_dir;
-    let t2 = (max - local_ray.origin) * inv_dir;
+    commands.entity(cube_entity).remove::<DeferredDrag>();
+
+    // The cube is a root entity, so its settled transform is already the global one, even before
+    // transforms are propagated this frame
+    let (camera, camera_transform) = *camera;
+    match pending_drag(
+        &deferred_drag.pick,
+        deferred_drag.viewport_origin,
+        camera,
+        camera_transform,
+        &GlobalTransform::from(*cube_transform),
+    ) {
+        Ok(pending_drag) => {
+            commands.entity(cube_entity).insert(pending_drag);
+        }
+        Err(failure) => {
+            debug::log_pick_failure(&debug_options, failure);
+            cube_events.write(CubeEvent::DragAborted);
+        }
+    }
+}
 
-    let t_min = t1.min(t2);
-    let t_max = t1.max(t2);
+fn pending_drag(
+    pick: &CubiePick,
+    viewport_origin: Vec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    cube_transform: &GlobalTransform,
+) -> Result<PendingDrag, PickFailure> {
+    let [
+        (axis_0, index_0, viewport_dir_0),
+        (axis_1, index_1, viewport_dir_1),
+    ] = pick_layer_candidates(pick, camera, camera_transform, cube_transform)?;
+
+    Ok(PendingDrag {
+        viewport_origin,
+        axis_0,
+        index_0,
+        viewport_dir_0,
+        axis_1,
+        index_1,
+        viewport_dir_1,
+    })
+}
 
-    let t_enter = t_min.max_element();
-    let t_exit = t_max.min_element();
+// A click makes the held turn, Escape settles the layer back where it was grabbed
+fn confirm_turn_system(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    preferences: Res<Preferences>,
+    cube: Single<(Entity, &PendingTurn), With<Cube>>,
+    mut cube_events: MessageWriter<CubeEvent>,
+) {
+    let (cube_entity, pending_turn) = *cube;
 
-    if t_enter > t_exit || t_exit <= 0.0 {
+    let target_rotations = if keys.just_pressed(CANCEL_TURN_KEY) {
+        cube_events.write(CubeEvent::DragAborted);
+        0
+    } else if mouse_buttons.just_pressed(MouseButton::Left) {
+        pending_turn.target_rotations
+    } else {
         return;
+    };
+
+    commands
+        .entity(cube_entity)
+        .remove::<PendingTurn>()
+        .insert(ActiveCubeRotation {
+            axis: pending_turn.axis,
+            current_angle: pending_turn.current_angle,
+            target_rotations,
+            speed: ROTATION_SPEED,
+            overshoot: turn_overshoot(&preferences, pending_turn.current_angle, target_rotations),
+        });
+}
+
+// Rotation of the whole cube that turns the picked face towards the camera until it's at least
+// DRAG_ASSIST_FACING head-on, None if it already is
+fn drag_assist_rotation(
+    pick: &CubiePick,
+    camera_transform: &GlobalTransform,
+    cube_transform: &GlobalTransform,
+) -> Option<Quat> {
+    let normal = cube_transform.rotation() * pick.face.normal();
+    let to_camera = (camera_transform.translation() - cube_transform.transform_point(pick.point))
+        .normalize_or_zero();
+
+    let facing = normal.dot(to_camera);
+    if facing >= DRAG_ASSIST_FACING {
+        return None;
     }
 
-    let hit = local_ray.get_point(t_enter);
+    let axis = normal.cross(to_camera).try_normalize()?;
+    let angle = facing.clamp(-1.0, 1.0).acos() - DRAG_ASSIST_FACING.acos();
+    Some(Quat::from_axis_angle(axis, angle) * cube_transform.rotation())
+}
 
-    const EPS: f32 = 1e-4;
+// Each scroll turns the outer layer of the hovered face a quarter turn, clockwise as seen from
+// that face when scrolling up. Notches that arrive while a layer is still turning wait their turn
+// in `QueuedTurns`. Only the drag scheme has them, select and turn moves nothing it hasn't selected.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn scroll_turn_system(
+    mut commands: Commands,
+    mut wheel_events: MessageReader<MouseWheel>,
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube: Single<
+        (Entity, &GlobalTransform, Has<ActiveCubeRotation>),
+        (
+            With<Cube>,
+            Without<ActiveDrag>,
+            Without<DeferredDrag>,
+            Without<PendingDrag>,
+            Without<PendingTurn>,
+        ),
+    >,
+    cubies: Query<(&Cubie, &Transform, &CubieBounds)>,
+    layer_cubies: Query<(Entity, &Cubie, &Transform)>,
+    time: Res<Time>,
+    preferences: Res<Preferences>,
+    last_move_completion: Res<LastMoveCompletion>,
+    exploded_view: Res<exploded::ExplodedView>,
+    mut queued_turns: ResMut<QueuedTurns>,
+) {
+    let scroll: f32 = wheel_events.read().map(|event| event.y).sum();
+    if !preferences.scroll_turns
+        || preferences.control_scheme != ControlScheme::Drag
+        || scroll == 0.0
+        || exploded_view.blocks_moves()
+    {
+        return;
+    }
 
-    let hit_face = if (hit.x - 0.5).abs() < EPS {
-        CubeFace::PosX
-    } else if (hit.x + 0.5).abs() < EPS {
-        CubeFace::NegX
-    } else if (hit.y - 0.5).abs() < EPS {
-        CubeFace::PosY
-    } else if (hit.y + 0.5).abs() < EPS {
-        CubeFace::NegY
-    } else if (hit.z - 0.5).abs() < EPS {
-        CubeFace::PosZ
-    } else {
-        CubeFace::NegZ
+    let (cube_entity, cube_transform, turning) = *cube;
+    let waits = turning || !queued_turns.0.is_empty();
+    if waits && queued_turns.0.len() >= MAX_QUEUED_TURNS
+        || !waits && move_on_cooldown(&time, &preferences, &last_move_completion)
+    {
+        return;
+    }
+
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
     };
 
-    let hit_up_direction = match hit_face {
-        CubeFace::PosX => Vec3::Y,
-        CubeFace::NegX => -Vec3::Y,
-        CubeFace::PosY => Vec3::Z,
-        CubeFace::NegY => -Vec3::Z,
-        CubeFace::PosZ => -Vec3::Y,
-        CubeFace::NegZ => Vec3::Y,
+    let (camera, camera_transform) = *camera;
+    let Ok(pick) = cursor_pick(
+        cursor_position,
+        camera,
+        camera_transform,
+        cube_transform,
+        cubies,
+    ) else {
+        return;
     };
 
-    let hit_right_direction = match hit_face {
-        CubeFace::PosX => -Vec3::Z,
-        CubeFace::NegX => Vec3::Z,
-        CubeFace::PosY => -Vec3::X,
-        CubeFace::NegY => Vec3::X,
-        CubeFace::PosZ => Vec3::X,
-        CubeFace::NegZ => -Vec3::X,
+    let (axis, index, positive_face) = match pick.face {
+        CubeFace::PosX => (CubeAxis::X, 2, true),
+        CubeFace::NegX => (CubeAxis::X, 0, false),
+        CubeFace::PosY => (CubeAxis::Y, 2, true),
+        CubeFace::NegY => (CubeAxis::Y, 0, false),
+        CubeFace::PosZ => (CubeAxis::Z, 2, true),
+        CubeFace::NegZ => (CubeAxis::Z, 0, false),
     };
 
-    let viewport_origin = cursor_position;
-
-    let (viewport_dir_0, viewport_dir_1) = {
-        let a_local = hit;
-        let b_local = hit + hit_up_direction * 0.1;
-        let c_local = hit + hit_right_direction * 0.1;
-        let a_world = cube.1.transform_point(a_local);
-        let b_world = cube.1.transform_point(b_local);
-        let c_world = cube.1.transform_point(c_local);
-        let a_viewport = match camera.world_to_viewport(global_transform, a_world) {
-            Ok(pos) => pos,
-            Err(_) => {
-                return;
-            }
-        };
-        let b_viewport = match camera.world_to_viewport(global_transform, b_world) {
-            Ok(pos) => pos,
-            Err(_) => {
-                return;
-            }
-        };
-        let c_viewport = match camera.world_to_viewport(global_transform, c_world) {
-            Ok(pos) => pos,
-            Err(_) => {
-                return;
-            }
-        };
-        (
-            (b_viewport - a_viewport).normalize_or_zero(),
-            (c_viewport - a_viewport).normalize_or_zero(),
-        )
+    // Clockwise seen from a face is a negative turn around its outward normal
+    let turn = QueuedTurn {
+        axis,
+        layers: turned_layers(
+            target_layer(index, &keys, &preferences),
+            &keys,
+            &preferences,
+        ),
+        target_rotations: if (scroll < 0.0) == positive_face {
+            1
+        } else {
+            3
+        },
     };
 
-    if viewport_dir_0 == Vec2::ZERO || viewport_dir_1 == Vec2::ZERO {
-        return;
-    }
+    if waits {
+        queued_turns.0.push_back(turn);
+    } else {
+        start_turn(
+            &mut commands,
+            cube_entity,
+            &layer_cubies,
+            turn,
+            &preferences,
+        );
+    }
+}
+
+// Starts the next queued turn as soon as the one before it settles. They were asked for before it
+// did, so the move cooldown doesn't hold them back.
+fn queued_turn_system(
+    mut commands: Commands,
+    cube: Single<Entity, IdleCube>,
+    layer_cubies: Query<(Entity, &Cubie, &Transform)>,
+    preferences: Res<Preferences>,
+    exploded_view: Res<exploded::ExplodedView>,
+    mut queued_turns: ResMut<QueuedTurns>,
+) {
+    if exploded_view.blocks_moves() {
+        queued_turns.0.clear();
+        return;
+    }
+
+    if let Some(turn) = queued_turns.0.pop_front() {
+        start_turn(&mut commands, *cube, &layer_cubies, turn, &preferences);
+    }
+}
+
+fn start_turn(
+    commands: &mut Commands,
+    cube: Entity,
+    cubies: &Query<(Entity, &Cubie, &Transform)>,
+    turn: QueuedTurn,
+    preferences: &Preferences,
+) {
+    grab_layers(commands, cube, cubies, turn.axis, turn.layers);
+    commands.entity(cube).insert(ActiveCubeRotation {
+        axis: turn.axis,
+        current_angle: 0.0,
+        target_rotations: turn.target_rotations,
+        speed: ROTATION_SPEED,
+        overshoot: turn_overshoot(preferences, 0.0, turn.target_rotations),
+    });
+}
+
+fn move_on_cooldown(
+    time: &Time,
+    preferences: &Preferences,
+    last_move_completion: &LastMoveCompletion,
+) -> bool {
+    last_move_completion.0.is_some_and(|completed_at| {
+        time.elapsed_secs() - completed_at < preferences.move_cooldown_secs
+    })
+}
 
-    let hit_face_uv = match hit_face {
-        CubeFace::PosX | CubeFace::NegX => Vec2::new(hit.z, hit.y), // (-0.5, -0.5) is bottom-right facing +X
-        CubeFace::PosY | CubeFace::NegY => Vec2::new(hit.x, hit.z), // (-0.5, -0.5) is back-left facing +Y
-        CubeFace::PosZ | CubeFace::NegZ => Vec2::new(hit.x, hit.y), // (-0.5, -0.5) is bottom-left facing +Z
-    } + Vec2::splat(0.5); // Map from [-0.5, 0.5] to [0, 1]
+fn cursor_pick<'a>(
+    cursor_position: Vec2,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    cube_transform: &GlobalTransform,
+    cubies: impl IntoIterator<Item = (&'a Cubie, &'a Transform, &'a CubieBounds)>,
+) -> Result<CubiePick, PickFailure> {
+    let ray = camera
+        .viewport_to_world(camera_transform, cursor_position)
+        .map_err(|_| PickFailure::RayCast)?;
+
+    let inv = cube_transform.affine().inverse();
+
+    pick_cubie(
+        inv.transform_point3(ray.origin),
+        inv.transform_vector3(ray.direction.as_vec3()),
+        cubies,
+    )
+    .ok_or(PickFailure::Missed)
+}
 
-    let (hit_face_cell_u, hit_face_cell_v) = (
-        (hit_face_uv.x * 3.0).floor() as u32,
-        (hit_face_uv.y * 3.0).floor() as u32,
-    );
+// The two layers running through a picked sticker, each with the on-screen direction that turns it
+// by a positive angle around its axis
+fn pick_layer_candidates(
+    pick: &CubiePick,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    cube_transform: &GlobalTransform,
+) -> Result<[(CubeAxis, u32, Vec2); 2], PickFailure> {
+    let hit = pick.point;
+    let hit_face = pick.face;
 
     let axis_0 = match hit_face {
         CubeFace::PosY | CubeFace::NegY | CubeFace::PosZ | CubeFace::NegZ => CubeAxis::X,
         CubeFace::PosX | CubeFace::NegX => CubeAxis::Z,
     };
 
-    let index_0 = hit_face_cell_u;
-
     let axis_1 = match hit_face {
         CubeFace::PosX | CubeFace::NegX | CubeFace::PosZ | CubeFace::NegZ => CubeAxis::Y,
         CubeFace::PosY | CubeFace::NegY => CubeAxis::Z,
     };
 
-    let index_1 = hit_face_cell_v;
+    // Projects the way the picked point actually moves when the layer starts turning. Away from a
+    // face's center that motion leaves the face plane, so the face's own edges aren't a good guess.
+    // Working in viewport pixels keeps the directions correct for any aspect ratio.
+    let viewport_direction = |axis: CubeAxis| -> Result<Vec2, PickFailure> {
+        let from_world = cube_transform.transform_point(hit);
+        let to_world = cube_transform.transform_point(hit + axis.unit().cross(hit) * 0.01);
+        let from_viewport = camera
+            .world_to_viewport(camera_transform, from_world)
+            .map_err(|_| PickFailure::Projection)?;
+        let to_viewport = camera
+            .world_to_viewport(camera_transform, to_world)
+            .map_err(|_| PickFailure::Projection)?;
+        Ok((to_viewport - from_viewport).normalize_or_zero())
+    };
 
-    commands.entity(cube.0).insert(PendingDrag {
-        viewport_origin,
-        axis_0,
-        index_0,
-        viewport_dir_0,
-        axis_1,
-        index_1,
-        viewport_dir_1,
-    });
+    let viewport_dir_0 = viewport_direction(axis_0)?;
+    let viewport_dir_1 = viewport_direction(axis_1)?;
+
+    if viewport_dir_0 == Vec2::ZERO || viewport_dir_1 == Vec2::ZERO {
+        return Err(PickFailure::EdgeOn);
+    }
+
+    Ok([
+        (axis_0, layer_index(pick.position, axis_0), viewport_dir_0),
+        (axis_1, layer_index(pick.position, axis_1), viewport_dir_1),
+    ])
+}
+
+// With the modifier held, a turn targets the parallel layer on the far side of the cube. The middle
+// layer is its own opposite.
+fn target_layer(index: u32, keys: &ButtonInput<KeyCode>, preferences: &Preferences) -> u32 {
+    if preferences.opposite_layer_modifier && keys.any_pressed(OPPOSITE_LAYER_KEYS) {
+        2 - index
+    } else {
+        index
+    }
+}
+
+// Layers a turn grabbed at `index` moves. With the wide turn modifier an outer layer takes the
+// middle one with it, the middle layer alone has no wide turn.
+fn turned_layers(
+    index: u32,
+    keys: &ButtonInput<KeyCode>,
+    preferences: &Preferences,
+) -> RangeInclusive<u32> {
+    let wide = preferences.wide_turn_modifier && keys.any_pressed(WIDE_TURN_KEYS);
+    match index {
+        0 if wide => 0..=1,
+        2 if wide => 1..=2,
+        _ => index..=index,
+    }
+}
+
+fn layer_index(position: (u32, u32, u32), axis: CubeAxis) -> u32 {
+    match axis {
+        CubeAxis::X => position.0,
+        CubeAxis::Y => position.1,
+        CubeAxis::Z => position.2,
+    }
+}
+
+// Marks every cubie in the layers so the drag and settle systems move them, and the cube when
+// they're more than one
+fn grab_layers(
+    commands: &mut Commands,
+    cube: Entity,
+    cubies: &Query<(Entity, &Cubie, &Transform)>,
+    axis: CubeAxis,
+    layers: RangeInclusive<u32>,
+) {
+    if layers.start() != layers.end() {
+        commands.entity(cube).insert(WideTurn);
+    }
+
+    for (cubie_entity, cubie, cubie_transform) in cubies.iter() {
+        if layers.contains(&layer_index(cubie.position, axis)) {
+            commands.entity(cubie_entity).insert(BeingDragged {
+                prev_rotation: cubie_transform.rotation,
+            });
+        }
+    }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn cubie_drag_pending_system(
     mut commands: Commands,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
-    window: Single<&Window>,
+    keys: Res<ButtonInput<KeyCode>>,
+    preferences: Res<Preferences>,
+    window: Single<&Window, With<PrimaryWindow>>,
     cube: Single<
         (Entity, &PendingDrag),
         (With<Cube>, Without<ActiveDrag>, Without<ActiveCubeRotation>),
     >,
     cubies: Query<(Entity, &Cubie, &Transform)>,
+    mut cube_events: MessageWriter<CubeEvent>,
 ) {
     if !mouse_buttons.pressed(MouseButton::Left) {
         commands.entity(cube.0).remove::<PendingDrag>();
+        cube_events.write(CubeEvent::DragAborted);
         return;
     }
 
@@ -309,15 +1068,20 @@ fn cubie_drag_pending_system(
         Some(pos) => pos,
         None => {
             commands.entity(cube.0).remove::<PendingDrag>();
+            cube_events.write(CubeEvent::DragAborted);
             return;
         }
     };
 
     const EPS: f32 = 1e-2;
 
-    let drag_vector = cursor_position - cube.1.viewport_origin;
+    let drag_vector = handed_drag(
+        cursor_position - cube.1.viewport_origin,
+        preferences.handedness,
+    );
 
-    if drag_vector.length() < EPS {
+    // Inside the dead zone the press is still just a click, releasing it won't turn anything
+    if drag_vector.length() < preferences.drag_dead_zone_px.max(EPS) {
         return;
     }
 
@@ -334,63 +1098,36 @@ fn cubie_drag_pending_system(
     }
 
     let (axis, index, viewport_dir) = if drag_dir_0_proj_length > drag_dir_1_proj_length {
-        (
-            cube.1.axis_0.clone(),
-            cube.1.index_0,
-            cube.1.viewport_dir_0.clone(),
-        )
+        (cube.1.axis_0, cube.1.index_0, cube.1.viewport_dir_0)
     } else {
-        (
-            cube.1.axis_1.clone(),
-            cube.1.index_1,
-            cube.1.viewport_dir_1.clone(),
-        )
+        (cube.1.axis_1, cube.1.index_1, cube.1.viewport_dir_1)
     };
 
-    match axis {
-        CubeAxis::X => {
-            for cubie in cubies.iter() {
-                if cubie.1.position.0 == index {
-                    commands.entity(cubie.0).insert(BeingDragged {
-                        prev_rotation: cubie.2.rotation,
-                    });
-                }
-            }
-        }
-        CubeAxis::Y => {
-            for cubie in cubies.iter() {
-                if cubie.1.position.1 == index {
-                    commands.entity(cubie.0).insert(BeingDragged {
-                        prev_rotation: cubie.2.rotation,
-                    });
-                }
-            }
-        }
-        CubeAxis::Z => {
-            for cubie in cubies.iter() {
-                if cubie.1.position.2 == index {
-                    commands.entity(cubie.0).insert(BeingDragged {
-                        prev_rotation: cubie.2.rotation,
-                    });
-                }
-            }
-        }
-    }
+    let layers = turned_layers(
+        target_layer(index, &keys, &preferences),
+        &keys,
+        &preferences,
+    );
+    grab_layers(&mut commands, cube.0, &cubies, axis, layers);
 
     commands.entity(cube.0).insert(ActiveDrag {
         axis,
         viewport_origin: cube.1.viewport_origin,
         viewport_dir,
         current_angle: 0.0,
+        angular_velocity: 0.0,
     });
 
     commands.entity(cube.0).remove::<PendingDrag>();
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn cubie_drag_system(
     mut commands: Commands,
     mouse_buttons: Res<ButtonInput<MouseButton>>,
-    window: Single<&Window>,
+    time: Res<Time>,
+    preferences: Res<Preferences>,
+    window: Single<&Window, With<PrimaryWindow>>,
     cube: Single<
         (Entity, &mut ActiveDrag),
         (
@@ -399,15 +1136,45 @@ fn cubie_drag_system(
             Without<ActiveCubeRotation>,
         ),
     >,
-    mut dragged_cubies: Query<&mut Transform, With<BeingDragged>>,
+    mut dragged_cubies: Query<(&Cubie, &mut Transform), With<BeingDragged>>,
+    mut cube_events: MessageWriter<CubeEvent>,
+    toast: Option<Single<(&mut hud::Toast, &mut Text, &mut Visibility)>>,
 ) {
     if !mouse_buttons.pressed(MouseButton::Left) {
         commands.entity(cube.0).remove::<ActiveDrag>();
+        let (target_rotations, speed) = release_target(&cube.1, &preferences);
+
+        // With notation shown, a middle layer turn is named the way the player's convention has it
+        let middle_layer = dragged_cubies
+            .iter()
+            .all(|(cubie, _)| layer_index(cubie.position, cube.1.axis) == 1);
+        let slice_notation = Move {
+            axis: cube.1.axis,
+            layer: 1,
+            quarter_turns: target_rotations,
+        }
+        .slice_notation(preferences.slice_convention)
+        .filter(|_| middle_layer && preferences.show_face_labels);
+        if let (Some(notation), Some(toast)) = (slice_notation, toast) {
+            hud::show_toast(toast, notation);
+        }
+        if target_rotations == 0 {
+            cube_events.write(CubeEvent::DragAborted);
+        } else if preferences.confirm_turns {
+            commands.entity(cube.0).insert(PendingTurn {
+                axis: cube.1.axis,
+                current_angle: cube.1.current_angle.rem_euclid(2.0 * PI),
+                target_rotations,
+            });
+            return;
+        }
+
         commands.entity(cube.0).insert(ActiveCubeRotation {
             axis: cube.1.axis,
             current_angle: cube.1.current_angle.rem_euclid(2.0 * PI),
-            target_rotations: ((cube.1.current_angle / (PI / 2.0)).round() as i32).rem_euclid(4)
-                as u32,
+            target_rotations,
+            speed,
+            overshoot: turn_overshoot(&preferences, cube.1.current_angle, target_rotations),
         });
         return;
     }
@@ -419,12 +1186,15 @@ fn cubie_drag_system(
         }
     };
 
-    let (_cube_entity, mut active_drag) = cube.into_inner();
+    let (cube_entity, mut active_drag) = cube.into_inner();
 
     const DRAG_ANGLE_SENSITIVITY: f32 = 0.01;
 
     let intended_drag_angle = {
-        let to_cursor = cursor_position - active_drag.viewport_origin;
+        let to_cursor = handed_drag(
+            cursor_position - active_drag.viewport_origin,
+            preferences.handedness,
+        );
         to_cursor.dot(active_drag.viewport_dir) * DRAG_ANGLE_SENSITIVITY
     };
 
@@ -442,13 +1212,30 @@ fn cubie_drag_system(
 
     let rotation_quat = Quat::from_axis_angle(rotation_axis, drag_angle);
 
-    for mut cubie_transform in dragged_cubies.iter_mut() {
+    for (_, mut cubie_transform) in dragged_cubies.iter_mut() {
         cubie_transform.rotate_around(rotation_center, rotation_quat);
     }
 
+    if preferences.detent_feedback && crosses_quarter_turn(current_angle, intended_drag_angle) {
+        commands
+            .entity(cube_entity)
+            .insert(DetentPulse(Timer::from_seconds(
+                DETENT_PULSE_SECS,
+                TimerMode::Once,
+            )));
+    }
+
     active_drag.current_angle = intended_drag_angle;
+
+    let delta_secs = time.delta_secs();
+    if delta_secs > 0.0 {
+        let smoothing = 1.0 - (-DRAG_VELOCITY_SMOOTHING * delta_secs).exp();
+        active_drag.angular_velocity +=
+            (drag_angle / delta_secs - active_drag.angular_velocity) * smoothing;
+    }
 }
 
+#[allow(clippy::type_complexity)]
 fn cubie_rotation_system(
     mut commands: Commands,
     time: Res<Time>,
@@ -457,26 +1244,35 @@ fn cubie_rotation_system(
         (With<Cube>, Without<PendingDrag>, Without<ActiveDrag>),
     >,
     mut dragged_cubies: Query<(Entity, &mut Cubie, &mut Transform, &BeingDragged)>,
+    resting_cubies: Query<(&Cubie, &Transform), Without<BeingDragged>>,
+    mut last_move_completion: ResMut<LastMoveCompletion>,
+    move_callbacks: Option<ResMut<MoveCallbacks>>,
 ) {
     let (cube_entity, mut active_rotation) = cube.into_inner();
 
-    const ROTATION_SPEED: f32 = PI;
-
-    let target_angle = active_rotation.target_rotations as f32 * (PI / 2.0);
-    let angle_diff = if active_rotation.target_rotations == 0 {
-        let normal_angle_diff = 0.0 - active_rotation.current_angle;
-        let wrapped_angle_diff = 2.0 * PI - active_rotation.current_angle;
-        if normal_angle_diff.abs() < wrapped_angle_diff.abs() {
-            normal_angle_diff
-        } else {
-            wrapped_angle_diff
+    // A bouncing turn first swings past its target, then comes back to it like any other
+    let delta_angle = if active_rotation.overshoot != 0.0 {
+        let to_peak = angle_to_target(
+            active_rotation.current_angle,
+            active_rotation.target_rotations,
+        ) + active_rotation.overshoot;
+        let step = step_towards(to_peak, active_rotation.speed, time.delta_secs());
+        if step == to_peak {
+            active_rotation.overshoot = 0.0;
         }
+        step
     } else {
-        target_angle - active_rotation.current_angle
+        rotation_step(
+            active_rotation.current_angle,
+            active_rotation.target_rotations,
+            active_rotation.speed,
+            time.delta_secs(),
+        )
     };
 
-    let delta_angle =
-        angle_diff.abs().min(ROTATION_SPEED * time.delta_secs()) * angle_diff.signum();
+    let delta_secs = time.delta_secs().min(MAX_ROTATION_STEP_SECS);
+    active_rotation.speed =
+        ROTATION_SPEED.max(active_rotation.speed * (-MOMENTUM_DECAY * delta_secs).exp());
 
     let rotation_axis = match active_rotation.axis {
         CubeAxis::X => Vec3::X,
@@ -496,10 +1292,11 @@ fn cubie_rotation_system(
 
     active_rotation.current_angle += delta_angle;
 
-    const EPS: f32 = 1e-3;
-
-    if (active_rotation.current_angle - target_angle).abs() < EPS
-        || ((active_rotation.current_angle + EPS).rem_euclid(2.0 * PI) - target_angle).abs() < EPS
+    if active_rotation.overshoot == 0.0
+        && rotation_finished(
+            active_rotation.current_angle,
+            active_rotation.target_rotations,
+        )
     {
         let cubie_rotation_quat = Quat::from_axis_angle(
             match active_rotation.axis {
@@ -510,45 +1307,462 @@ fn cubie_rotation_system(
             (PI / 2.0) * active_rotation.target_rotations as f32,
         );
 
+        let mut turned_layers = Vec::new();
         for (cubie_entity, mut cubie_data, mut cubie_transform, being_dragged) in
             dragged_cubies.iter_mut()
         {
-            let (curr_x, curr_y, curr_z) = cubie_data.position;
-
-            cubie_data.position = match active_rotation.axis {
-                CubeAxis::X => match active_rotation.target_rotations {
-                    1 => (curr_x, 2 - curr_z, curr_y),
-                    2 => (curr_x, 2 - curr_y, 2 - curr_z),
-                    3 => (curr_x, curr_z, 2 - curr_y),
-                    _ => (curr_x, curr_y, curr_z),
-                },
-                CubeAxis::Y => match active_rotation.target_rotations {
-                    1 => (curr_z, curr_y, 2 - curr_x),
-                    2 => (2 - curr_x, curr_y, 2 - curr_z),
-                    3 => (2 - curr_z, curr_y, curr_x),
-                    _ => (curr_x, curr_y, curr_z),
-                },
-                CubeAxis::Z => match active_rotation.target_rotations {
-                    1 => (2 - curr_y, curr_x, curr_z),
-                    2 => (2 - curr_x, 2 - curr_y, curr_z),
-                    3 => (curr_y, 2 - curr_x, curr_z),
-                    _ => (curr_x, curr_y, curr_z),
-                },
-            };
+            let layer = layer_index(cubie_data.position, active_rotation.axis);
+            if !turned_layers.contains(&layer) {
+                turned_layers.push(layer);
+            }
 
-            let (new_x, new_y, new_z) = cubie_data.position;
+            cubie_data.position = rotate_position(
+                cubie_data.position,
+                active_rotation.axis,
+                active_rotation.target_rotations,
+            );
 
-            cubie_transform.translation =
-                (Vec3::new(new_x as f32, new_y as f32, new_z as f32) - 1.0) / 3.0;
+            cubie_transform.translation = slot_translation(cubie_data.position);
             cubie_transform.rotation = cubie_rotation_quat.mul_quat(being_dragged.prev_rotation);
             commands.entity(cubie_entity).remove::<BeingDragged>();
         }
 
-        commands.entity(cube_entity).remove::<ActiveCubeRotation>();
+        commands
+            .entity(cube_entity)
+            .remove::<(ActiveCubeRotation, WideTurn)>();
+        if active_rotation.target_rotations != 0 {
+            last_move_completion.0 = Some(time.elapsed_secs());
+
+            if let Some(mut move_callbacks) =
+                move_callbacks.filter(|callbacks| !callbacks.is_empty())
+            {
+                turned_layers.sort();
+                let moves = turned_layers
+                    .into_iter()
+                    .map(|layer| Move {
+                        axis: active_rotation.axis,
+                        layer,
+                        quarter_turns: active_rotation.target_rotations,
+                    })
+                    .collect::<Vec<_>>();
+
+                let cubies = dragged_cubies
+                    .iter()
+                    .map(|(_, cubie, transform, _)| (cubie.position, transform.rotation))
+                    .chain(
+                        resting_cubies
+                            .iter()
+                            .map(|(cubie, transform)| (cubie.position, transform.rotation)),
+                    );
+                move_callbacks.run(&moves, CubeState::from_cubies(cubies));
+            }
+        }
+    }
+}
+
+// Drag as the turn mapping sees it, left-handed play mirrors it left to right
+fn handed_drag(drag_vector: Vec2, handedness: Handedness) -> Vec2 {
+    match handedness {
+        Handedness::Right => drag_vector,
+        Handedness::Left => Vec2::new(-drag_vector.x, drag_vector.y),
+    }
+}
+
+// Where a cubie in the given slot sits in the cube's local space
+fn slot_translation(position: (u32, u32, u32)) -> Vec3 {
+    (Vec3::new(position.0 as f32, position.1 as f32, position.2 as f32) - 1.0) / 3.0
+}
+
+// Quarter turn the dragged layer settles on if it's let go now, and the speed it starts settling at
+fn release_target(active_drag: &ActiveDrag, preferences: &Preferences) -> (u32, f32) {
+    let (target_rotations, speed) = if preferences.layer_momentum {
+        release_with_momentum(active_drag.current_angle, active_drag.angular_velocity)
+    } else {
+        (
+            nearest_quarter_turns(active_drag.current_angle),
+            ROTATION_SPEED,
+        )
+    };
+
+    if preferences.easy_turns {
+        (
+            assisted_quarter_turns(active_drag.current_angle, target_rotations),
+            speed,
+        )
+    } else {
+        (target_rotations, speed)
+    }
+}
+
+// Number of counterclockwise quarter turns (0..4) closest to the given angle
+fn nearest_quarter_turns(angle: f32) -> u32 {
+    ((angle / (PI / 2.0)).round() as i32).rem_euclid(4) as u32
+}
+
+// Signed shortest angle from `current_angle` to the quarter turn `target_rotations`, in (-PI, PI]
+fn angle_to_target(current_angle: f32, target_rotations: u32) -> f32 {
+    let angle_diff = (target_rotations as f32 * (PI / 2.0) - current_angle).rem_euclid(2.0 * PI);
+    if angle_diff > PI {
+        angle_diff - 2.0 * PI
+    } else {
+        angle_diff
+    }
+}
+
+fn rotation_step(current_angle: f32, target_rotations: u32, speed: f32, delta_secs: f32) -> f32 {
+    step_towards(
+        angle_to_target(current_angle, target_rotations),
+        speed,
+        delta_secs,
+    )
+}
+
+fn step_towards(angle_diff: f32, speed: f32, delta_secs: f32) -> f32 {
+    let max_step = speed * delta_secs.min(MAX_ROTATION_STEP_SECS);
+    angle_diff.abs().min(max_step) * angle_diff.signum()
+}
+
+// Swing past the target for a turn starting at `current_angle`, in the direction it's heading. Short
+// settles swing no further than they travel.
+fn turn_overshoot(preferences: &Preferences, current_angle: f32, target_rotations: u32) -> f32 {
+    match preferences.turn_easing {
+        TurnEasing::Linear => 0.0,
+        TurnEasing::Bounce => {
+            angle_to_target(current_angle, target_rotations).clamp(-TURN_OVERSHOOT, TURN_OVERSHOOT)
+        }
+    }
+}
+
+// Target and starting speed for a layer released while moving. The layer coasts towards the quarter
+// turn it would reach shortly, but never more than one quarter turn past where it was let go.
+fn release_with_momentum(angle: f32, angular_velocity: f32) -> (u32, f32) {
+    let coast = (angular_velocity * MOMENTUM_SECS).clamp(-PI / 2.0, PI / 2.0);
+    let target_rotations = nearest_quarter_turns(angle + coast);
+
+    // Only keep the speed if the layer was already moving towards its target
+    let heading = angle_to_target(angle, target_rotations) * angular_velocity;
+    let speed = if heading > 0.0 {
+        ROTATION_SPEED.max(angular_velocity.abs())
+    } else {
+        ROTATION_SPEED
+    };
+
+    (target_rotations, speed)
+}
+
+// Short drags that would snap back still turn the layer a quarter turn the way it was dragged
+fn assisted_quarter_turns(angle: f32, target_rotations: u32) -> u32 {
+    if target_rotations != 0 || angle == 0.0 || angle.abs() > PI {
+        target_rotations
+    } else if angle > 0.0 {
+        1
+    } else {
+        3
+    }
+}
+
+fn crosses_quarter_turn(from_angle: f32, to_angle: f32) -> bool {
+    (from_angle / (PI / 2.0)).floor() != (to_angle / (PI / 2.0)).floor()
+}
+
+fn detent_pulse_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    cube: Single<(Entity, &mut Transform, &mut DetentPulse), With<Cube>>,
+) {
+    let (cube_entity, mut cube_transform, mut pulse) = cube.into_inner();
+
+    pulse.0.tick(time.delta());
+
+    if pulse.0.is_finished() {
+        cube_transform.scale = Vec3::ONE;
+        commands.entity(cube_entity).remove::<DetentPulse>();
+        return;
+    }
+
+    cube_transform.scale = Vec3::splat(1.0 + DETENT_PULSE_SCALE * (1.0 - pulse.0.fraction()));
+}
+
+fn rotation_finished(current_angle: f32, target_rotations: u32) -> bool {
+    const EPS: f32 = 1e-3;
+
+    angle_to_target(current_angle, target_rotations).abs() < EPS
+}
+
+// Logical position of a cubie after `quarter_turns` counterclockwise quarter turns around `axis`
+fn rotate_position(
+    position: (u32, u32, u32),
+    axis: CubeAxis,
+    quarter_turns: u32,
+) -> (u32, u32, u32) {
+    let (x, y, z) = position;
+
+    match axis {
+        CubeAxis::X => match quarter_turns {
+            1 => (x, 2 - z, y),
+            2 => (x, 2 - y, 2 - z),
+            3 => (x, z, 2 - y),
+            _ => (x, y, z),
+        },
+        CubeAxis::Y => match quarter_turns {
+            1 => (z, y, 2 - x),
+            2 => (2 - x, y, 2 - z),
+            3 => (2 - z, y, x),
+            _ => (x, y, z),
+        },
+        CubeAxis::Z => match quarter_turns {
+            1 => (2 - y, x, z),
+            2 => (2 - x, 2 - y, z),
+            3 => (y, 2 - x, z),
+            _ => (x, y, z),
+        },
+    }
+}
+
+fn toggle_sticker_style_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut preferences: ResMut<Preferences>,
+) {
+    if !keys.just_pressed(TOGGLE_STICKERS_KEY) {
+        return;
+    }
+
+    let stickers = &mut preferences.cube_style.stickers;
+    *stickers = match stickers {
+        StickerStyle::Stickered => StickerStyle::Stickerless,
+        StickerStyle::Stickerless => StickerStyle::Stickered,
+    };
+}
+
+fn toggle_x_ray_system(keys: Res<ButtonInput<KeyCode>>, mut preferences: ResMut<Preferences>) {
+    if keys.just_pressed(TOGGLE_X_RAY_KEY) {
+        preferences.cube_style.x_ray = !preferences.cube_style.x_ray;
+    }
+}
+
+fn apply_cube_style_system(
+    mut commands: Commands,
+    preferences: Res<Preferences>,
+    mut cubie_material: ResMut<CubieMaterial>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    cubies: Query<(Entity, &Mesh3d), With<Cubie>>,
+    outlines: Query<Entity, With<CubieOutline>>,
+) {
+    if !shows_outlines(&preferences.cube_style) {
+        for outline in &outlines {
+            commands.entity(outline).despawn();
+        }
+    } else if outlines.is_empty() {
+        for (cubie, mesh) in &cubies {
+            commands.entity(cubie).with_child(cubie_outline(
+                mesh.0.clone(),
+                cubie_material.outline_material.clone(),
+            ));
+        }
+    }
+
+    cubie_material.custom_border_texture = preferences
+        .cube_style
+        .border_width
+        .map(|border_width| images.add(cubie_face_image(border_width)));
+
+    refresh_cubie_material(&preferences.cube_style, &cubie_material, &mut materials);
+}
+
+// Keeps the game playable when the bundled face texture can't be loaded, e.g. from a broken
+// package, by swapping in a generated texture with the same look
+fn border_texture_fallback_system(
+    mut load_failures: MessageReader<AssetLoadFailedEvent<Image>>,
+    preferences: Res<Preferences>,
+    mut cubie_material: ResMut<CubieMaterial>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for failure in load_failures.read() {
+        if failure.id != cubie_material.border_texture.id() {
+            continue;
+        }
+
+        warn!(
+            "Failed to load {}, using a generated face texture instead: {}",
+            failure.path, failure.error
+        );
+
+        cubie_material.border_texture = images.add(cubie_face_image(BUNDLED_BORDER_WIDTH));
+        refresh_cubie_material(&preferences.cube_style, &cubie_material, &mut materials);
+    }
+}
+
+fn refresh_cubie_material(
+    style: &CubeStyle,
+    cubie_material: &CubieMaterial,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let Some(material) = materials.get_mut(&cubie_material.material) else {
+        return;
+    };
+
+    *material = cubie_standard_material(
+        style,
+        cubie_material
+            .custom_border_texture
+            .as_ref()
+            .unwrap_or(&cubie_material.border_texture),
+    );
+}
+
+fn cubie_standard_material(style: &CubeStyle, border_texture: &Handle<Image>) -> StandardMaterial {
+    let mut material = StandardMaterial {
+        base_color_texture: match style.stickers {
+            StickerStyle::Stickered => Some(border_texture.clone()),
+            StickerStyle::Stickerless => None, // Whole face is the plastic color
+        },
+        unlit: true,
+        ..Default::default()
+    };
+
+    if style.x_ray {
+        material.base_color = X_RAY_COLOR;
+        material.alpha_mode = AlphaMode::Blend;
+        // The faces turned away from the camera are the ones worth seeing through the cube
+        material.cull_mode = None;
+        material.double_sided = true;
+    }
+
+    material
+}
+
+// See-through cubies would show the outlines of the ones behind them, so x-ray goes without
+fn shows_outlines(style: &CubeStyle) -> bool {
+    style.outlines && !style.x_ray
+}
+
+// Shares its cubie's mesh, so recoloring the stickers leaves the outline as it is
+fn cubie_outline(mesh: Handle<Mesh>, material: Handle<StandardMaterial>) -> impl Bundle {
+    (
+        CubieOutline,
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::from_scale(Vec3::splat(OUTLINE_SCALE)),
+    )
+}
+
+// Same layout as the bundled cubie_face.png, with a rounded sticker that leaves `border_width` of
+// the face on each side. The sticker edge is anti-aliased over a pixel.
+fn cubie_face_image(border_width: f32) -> Image {
+    let half_extent = 0.5 - border_width.clamp(0.0, 0.5);
+    let corner_radius = (border_width * BORDER_CORNER_ROUNDNESS).min(half_extent);
+
+    let mut data = Vec::with_capacity((2 * FACE_TEXTURE_SIZE * FACE_TEXTURE_SIZE * 4) as usize);
+    for y in 0..FACE_TEXTURE_SIZE {
+        for x in 0..2 * FACE_TEXTURE_SIZE {
+            let shade = if x < FACE_TEXTURE_SIZE {
+                let point = (Vec2::new(x as f32, y as f32) + 0.5) / FACE_TEXTURE_SIZE as f32 - 0.5;
+
+                // Signed distance to the rounded sticker, negative inside
+                let corner_offset = point.abs() - Vec2::splat(half_extent - corner_radius);
+                let distance = corner_offset.max(Vec2::ZERO).length()
+                    + corner_offset.max_element().min(0.0)
+                    - corner_radius;
+
+                let coverage = (0.5 - distance * FACE_TEXTURE_SIZE as f32).clamp(0.0, 1.0);
+                (coverage * 255.0).round() as u8
+            } else {
+                FLAT_FACE_SHADE
+            };
+
+            data.extend_from_slice(&[shade, shade, shade, 255]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: 2 * FACE_TEXTURE_SIZE,
+            height: FACE_TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::ClampToEdge,
+        address_mode_v: ImageAddressMode::ClampToEdge,
+        min_filter: ImageFilterMode::Linear,
+        mag_filter: ImageFilterMode::Linear,
+        ..default()
+    });
+
+    image
+}
+
+fn cubie_bounds(shape: CubeShape, position: (u32, u32, u32)) -> CubieBounds {
+    match shape {
+        CubeShape::Standard => CubieBounds {
+            min: Vec3::splat(-0.5),
+            max: Vec3::splat(0.5),
+        },
+        CubeShape::Mirror => {
+            const CUT: f32 = 1.0 / 6.0;
+
+            let layer_extent = |index: u32, neg_thickness: f32, pos_thickness: f32| match index {
+                0 => (-CUT - neg_thickness, -CUT),
+                1 => (-CUT, CUT),
+                _ => (CUT, CUT + pos_thickness),
+            };
+
+            let (x, y, z) = position;
+            let (min_x, max_x) = layer_extent(
+                x,
+                MIRROR_NEG_LAYER_THICKNESS.x,
+                MIRROR_POS_LAYER_THICKNESS.x,
+            );
+            let (min_y, max_y) = layer_extent(
+                y,
+                MIRROR_NEG_LAYER_THICKNESS.y,
+                MIRROR_POS_LAYER_THICKNESS.y,
+            );
+            let (min_z, max_z) = layer_extent(
+                z,
+                MIRROR_NEG_LAYER_THICKNESS.z,
+                MIRROR_POS_LAYER_THICKNESS.z,
+            );
+
+            // From cube space to the cubie's local space (centered on its slot, scaled by 3)
+            let center = (Vec3::new(x as f32, y as f32, z as f32) - 1.0) / 3.0;
+            CubieBounds {
+                min: (Vec3::new(min_x, min_y, min_z) - center) * 3.0,
+                max: (Vec3::new(max_x, max_y, max_z) - center) * 3.0,
+            }
+        }
+    }
+}
+
+// Four vertices per face, in the order colored_cube_mesh lays the faces out
+fn cube_vertex_colors(colors: &CubeFaceColors) -> Vec<[f32; 4]> {
+    let mut vertex_colors: Vec<[f32; 4]> = vec![];
+
+    for face_color in [
+        colors.pos_z,
+        colors.neg_z,
+        colors.neg_x,
+        colors.pos_x,
+        colors.pos_y,
+        colors.neg_y,
+    ] {
+        let color = face_color.unwrap_or(CUBIE_BASE_COLOR).to_srgba();
+        let color_array = [color.red, color.green, color.blue, color.alpha];
+        for _ in 0..4 {
+            vertex_colors.push(color_array);
+        }
     }
+
+    vertex_colors
 }
 
-fn colored_cube_mesh(colors: CubeFaceColors) -> Mesh {
+fn colored_cube_mesh(colors: CubeFaceColors, bounds: &CubieBounds) -> Mesh {
     let mut mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
         RenderAssetUsages::RENDER_WORLD,
@@ -585,29 +1799,20 @@ fn colored_cube_mesh(colors: CubeFaceColors) -> Mesh {
         [0.5, -0.5, -0.5],
         [0.5, -0.5, 0.5],
         [-0.5, -0.5, 0.5],
-    ];
-
-    /*let colors = per_face_colors
-    .iter()
-    .flat_map(|color| vec![*color; 4])
-    .collect::<Vec<_>>();*/
-
-    let mut vertex_colors: Vec<[f32; 4]> = vec![];
+    ]
+    .into_iter()
+    .map(|corner| {
+        let corner = Vec3::from_array(corner) + 0.5; // From [-0.5, 0.5] to [0, 1]
+        (bounds.min + (bounds.max - bounds.min) * corner).to_array()
+    })
+    .collect::<Vec<_>>();
 
-    for face_color in [
-        colors.pos_z,
-        colors.neg_z,
-        colors.neg_x,
-        colors.pos_x,
-        colors.pos_y,
-        colors.neg_y,
-    ] {
-        let color = face_color.unwrap_or(Color::srgb(0.0, 0.0, 0.0)).to_srgba();
-        let color_array = [color.red, color.green, color.blue, color.alpha];
-        for _ in 0..4 {
-            vertex_colors.push(color_array);
-        }
-    }
+    /*let colors = per_face_colors
+    .iter()
+    .flat_map(|color| vec![*color; 4])
+    .collect::<Vec<_>>();*/
+
+    let vertex_colors = cube_vertex_colors(&colors);
 
     let normals = vec![[0.0, 0.0, 1.0]; 4]
         .into_iter()
@@ -723,10 +1928,15 @@ fn game_setup(
     assets: ResMut<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    preferences: Res<Preferences>,
 ) {
+    // Leaving the game drops the cube with whatever drag or turn it was in the middle of, so the
+    // next game starts from a solved cube
     commands.spawn((
+        DespawnOnExit(GameState::Game),
         Camera3d::default(),
-        Transform::from_xyz(0.0, 0.0, 3.0), //Transform::from_xyz(-3.0, 3.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+        camera_view_transform(preferences.camera_view),
     ));
 
     /*commands.spawn((
@@ -752,108 +1962,76 @@ fn game_setup(
         },
     );
 
-    let cubie_material = materials.add(StandardMaterial {
-        base_color_texture: Some(cubie_border_texture),
+    let custom_border_texture = preferences
+        .cube_style
+        .border_width
+        .map(|border_width| images.add(cubie_face_image(border_width)));
+
+    let cubie_material = materials.add(cubie_standard_material(
+        &preferences.cube_style,
+        custom_border_texture
+            .as_ref()
+            .unwrap_or(&cubie_border_texture),
+    ));
+
+    let outline_material = materials.add(StandardMaterial {
+        base_color: OUTLINE_COLOR,
         unlit: true,
-        ..Default::default()
+        cull_mode: Some(Face::Front),
+        ..default()
+    });
+
+    commands.insert_resource(CubieMaterial {
+        material: cubie_material.clone(),
+        border_texture: cubie_border_texture,
+        custom_border_texture,
+        outline_material: outline_material.clone(),
     });
 
+    let color_scheme = valid_color_scheme(&preferences.color_scheme);
+
     commands
         .spawn((
+            DespawnOnExit(GameState::Game),
             Cube,
             Visibility::Inherited,
-            Transform::from_rotation(Quat::from_euler(
-                EulerRot::XYZ,
-                /*PI / 6.0*/ 30.0_f32.to_radians(),
-                -PI / 4.0,
-                0.0,
-            )),
+            Transform::from_rotation(default_cube_rotation(preferences.camera_view)),
         ))
         .with_children(|parent| {
             for x in 0..3 {
                 for y in 0..3 {
                     for z in 0..3 {
-                        if x == 1 && y == 1 && z == 1 {
-                            continue; // Skip the center cubie
+                        if cubie_kind((x, y, z)) == CubieKind::Core {
+                            continue;
                         }
 
-                        parent.spawn((
+                        let bounds = cubie_bounds(preferences.cube_shape, (x, y, z));
+                        let colors = cubie_face_colors(
+                            preferences.cube_shape,
+                            &color_scheme,
+                            preferences.sticker_calibration,
+                            (x, y, z),
+                        );
+
+                        let mesh = meshes.add(colored_cube_mesh(colors, &bounds));
+
+                        let mut cubie = parent.spawn((
                             Cubie {
                                 position: (x, y, z),
+                                home_position: (x, y, z),
                             },
-                            Mesh3d(meshes.add(colored_cube_mesh(
-                                /*[
-                                    if z == 2 {
-                                        [1.0, 0.0, 0.0, 1.0] // Red
-                                    } else {
-                                        [0.0, 0.0, 0.0, 1.0]
-                                    },
-                                    if z == 0 {
-                                        [1.0, 0.2, 0.0, 1.0] // Orange
-                                    } else {
-                                        [0.0, 0.0, 0.0, 1.0]
-                                    },
-                                    if x == 0 {
-                                        [1.0, 1.0, 0.0, 1.0] // Yellow
-                                    } else {
-                                        [0.0, 0.0, 0.0, 1.0]
-                                    },
-                                    if x == 2 {
-                                        [1.0, 1.0, 1.0, 1.0] // White
-                                    } else {
-                                        [0.0, 0.0, 0.0, 1.0]
-                                    },
-                                    if y == 2 {
-                                        [0.0, 1.0, 0.0, 1.0] // Green
-                                    } else {
-                                        [0.0, 0.0, 0.0, 1.0]
-                                    },
-                                    if y == 0 {
-                                        [0.0, 0.0, 1.0, 1.0] // Blue
-                                    } else {
-                                        [0.0, 0.0, 0.0, 1.0]
-                                    },
-                                ]*/
-                                CubeFaceColors {
-                                    pos_x: if x == 2 {
-                                        Some(Color::srgb(1.0, 1.0, 1.0)) // White
-                                    } else {
-                                        None
-                                    },
-                                    neg_x: if x == 0 {
-                                        Some(Color::srgb(1.0, 1.0, 0.0)) // Yellow
-                                    } else {
-                                        None
-                                    },
-                                    pos_y: if y == 2 {
-                                        Some(Color::srgb(0.0, 1.0, 0.0)) // Green
-                                    } else {
-                                        None
-                                    },
-                                    neg_y: if y == 0 {
-                                        Some(Color::srgb(0.0, 0.0, 1.0)) // Blue
-                                    } else {
-                                        None
-                                    },
-                                    pos_z: if z == 2 {
-                                        Some(Color::srgb(1.0, 0.0, 0.0)) // Red
-                                    } else {
-                                        None
-                                    },
-                                    neg_z: if z == 0 {
-                                        Some(Color::srgb(1.0, 0.2, 0.0)) // Orange
-                                    } else {
-                                        None
-                                    },
-                                },
-                            ))),
+                            Mesh3d(mesh.clone()),
+                            bounds,
                             MeshMaterial3d(cubie_material.clone()),
                             Transform {
-                                translation: (Vec3::new(x as f32, y as f32, z as f32) - 1.0) / 3.0,
+                                translation: slot_translation((x, y, z)),
                                 scale: Vec3::splat(1.0 / 3.0),
                                 ..default()
                             },
                         ));
+                        if shows_outlines(&preferences.cube_style) {
+                            cubie.with_child(cubie_outline(mesh, outline_material.clone()));
+                        }
                     }
                 }
             }
@@ -862,6 +2040,1467 @@ fn game_setup(
     clear_color.0 = CLEAR_COLOR;
 }
 
-fn game_cleanup(mut _commands: Commands, mut clear_color: ResMut<ClearColor>) {
+fn face_sticker(color_scheme: &ColorScheme, face: CubeFace) -> StickerColor {
+    match face {
+        CubeFace::PosX => color_scheme.pos_x,
+        CubeFace::NegX => color_scheme.neg_x,
+        CubeFace::PosY => color_scheme.pos_y,
+        CubeFace::NegY => color_scheme.neg_y,
+        CubeFace::PosZ => color_scheme.pos_z,
+        CubeFace::NegZ => color_scheme.neg_z,
+    }
+}
+
+fn face_color(
+    shape: CubeShape,
+    color_scheme: &ColorScheme,
+    calibration: StickerCalibration,
+    face: CubeFace,
+) -> Color {
+    match shape {
+        CubeShape::Standard => calibration.apply(face_sticker(color_scheme, face).color()),
+        CubeShape::Mirror => MIRROR_COLOR,
+    }
+}
+
+// Whether a face of the cubie in the given slot is on the outside of the cube, and so has a sticker
+fn is_outer_face(position: (u32, u32, u32), face: CubeFace) -> bool {
+    let (x, y, z) = position;
+    match face {
+        CubeFace::PosX => x == 2,
+        CubeFace::NegX => x == 0,
+        CubeFace::PosY => y == 2,
+        CubeFace::NegY => y == 0,
+        CubeFace::PosZ => z == 2,
+        CubeFace::NegZ => z == 0,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CubieKind {
+    Core, // Hidden in the middle, never spawned
+    Center,
+    Edge,
+    Corner,
+}
+
+// Centers have one outer face, edges two and corners three
+fn cubie_kind(position: (u32, u32, u32)) -> CubieKind {
+    match CubeFace::ALL
+        .into_iter()
+        .filter(|face| is_outer_face(position, *face))
+        .count()
+    {
+        0 => CubieKind::Core,
+        1 => CubieKind::Center,
+        2 => CubieKind::Edge,
+        _ => CubieKind::Corner,
+    }
+}
+
+fn cubie_face_colors(
+    shape: CubeShape,
+    color_scheme: &ColorScheme,
+    calibration: StickerCalibration,
+    position: (u32, u32, u32),
+) -> CubeFaceColors {
+    let color = |face: CubeFace| {
+        is_outer_face(position, face).then(|| face_color(shape, color_scheme, calibration, face))
+    };
+
+    CubeFaceColors {
+        pos_x: color(CubeFace::PosX),
+        neg_x: color(CubeFace::NegX),
+        pos_y: color(CubeFace::PosY),
+        neg_y: color(CubeFace::NegY),
+        pos_z: color(CubeFace::PosZ),
+        neg_z: color(CubeFace::NegZ),
+    }
+}
+
+// Hand-edited preferences can repeat a color, fall back to the default scheme rather than spawning
+// a cube that can't be solved by sight
+fn valid_color_scheme(color_scheme: &ColorScheme) -> ColorScheme {
+    if color_scheme.has_distinct_colors() {
+        color_scheme.clone()
+    } else {
+        warn!("Stored color scheme repeats a color, using the default one");
+        ColorScheme::default()
+    }
+}
+
+// The cube and camera go with DespawnOnExit, this puts back what the game left in resources so the
+// next one doesn't start in the middle of the last one's moves
+fn game_cleanup(
+    mut clear_color: ResMut<ClearColor>,
+    mut last_move_completion: ResMut<LastMoveCompletion>,
+    mut queued_turns: ResMut<QueuedTurns>,
+    mut exploded_view: ResMut<exploded::ExplodedView>,
+) {
     clear_color.0 = ClearColor::default().0;
+    *last_move_completion = default();
+    *queued_turns = default();
+    *exploded_view = default();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use test_support::*;
+
+    fn slot_center(position: (u32, u32, u32)) -> Vec3 {
+        (Vec3::new(position.0 as f32, position.1 as f32, position.2 as f32) - 1.0) / 3.0
+    }
+
+    // Drives the settle animation the way cubie_rotation_system does, returning the target and the
+    // number of frames it took
+    fn settle(drag_angle: f32) -> (u32, u32) {
+        let target_rotations = nearest_quarter_turns(drag_angle);
+        let mut current_angle = drag_angle.rem_euclid(2.0 * PI);
+
+        for frame in 0..600 {
+            if rotation_finished(current_angle, target_rotations) {
+                return (target_rotations, frame);
+            }
+            current_angle +=
+                rotation_step(current_angle, target_rotations, ROTATION_SPEED, FRAME_SECS);
+        }
+
+        panic!("layer released at {drag_angle} rad never settled (stuck at {current_angle} rad)");
+    }
+
+    #[test]
+    fn released_layer_settles_to_nearest_quarter_turn() {
+        for (drag_degrees, expected_rotations) in [
+            (0.0, 0),
+            (3.0, 0),
+            (-4.0, 0),
+            (88.0, 1),
+            (93.0, 1),
+            (178.0, 2),
+            (185.0, 2),
+            (266.0, 3),
+            (272.0, 3),
+            (314.0, 3),
+            (316.0, 0),
+            (350.0, 0),
+            (359.9, 0),
+            (-80.0, 3),
+            (440.0, 1),
+        ] {
+            let (target_rotations, _) = settle(f32::to_radians(drag_degrees));
+            assert_eq!(
+                target_rotations, expected_rotations,
+                "drag released at {drag_degrees} degrees"
+            );
+        }
+    }
+
+    #[test]
+    fn wrapped_release_settles_forward_through_full_turn() {
+        // 350 degrees is 10 degrees away from a full turn, so it must not rotate 350 degrees back
+        let (_, frames) = settle(f32::to_radians(350.0));
+        let max_frames = (f32::to_radians(10.0) / (ROTATION_SPEED * FRAME_SECS)).ceil() as u32 + 1;
+        assert!(frames <= max_frames, "took {frames} frames");
+
+        assert!(angle_to_target(f32::to_radians(350.0), 0) > 0.0);
+        assert!(angle_to_target(f32::to_radians(10.0), 0) < 0.0);
+    }
+
+    #[test]
+    fn rotation_finishes_exactly_at_full_turn() {
+        assert!(rotation_finished(2.0 * PI, 0));
+        assert!(rotation_finished(2.0 * PI - 1e-5, 0));
+        assert!(rotation_finished(1e-5, 0));
+        assert!(rotation_finished(3.0 * PI / 2.0 + 1e-5, 3));
+        assert!(!rotation_finished(PI / 4.0, 0));
+    }
+
+    #[test]
+    fn rotate_position_matches_quaternion_rotation() {
+        for axis in [CubeAxis::X, CubeAxis::Y, CubeAxis::Z] {
+            let rotation_axis = match axis {
+                CubeAxis::X => Vec3::X,
+                CubeAxis::Y => Vec3::Y,
+                CubeAxis::Z => Vec3::Z,
+            };
+
+            for quarter_turns in 0..4 {
+                let rotation =
+                    Quat::from_axis_angle(rotation_axis, (PI / 2.0) * quarter_turns as f32);
+
+                for x in 0..3 {
+                    for y in 0..3 {
+                        for z in 0..3 {
+                            let rotated = rotate_position((x, y, z), axis, quarter_turns);
+                            let expected = rotation * slot_center((x, y, z));
+                            assert!(
+                                slot_center(rotated).abs_diff_eq(expected, 1e-5),
+                                "{:?} x{quarter_turns} moved {:?} to {:?}",
+                                axis,
+                                (x, y, z),
+                                rotated
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_release_leaves_positions_unchanged() {
+        let (target_rotations, _) = settle(f32::to_radians(350.0));
+
+        for position in [(0, 0, 0), (2, 1, 0), (1, 2, 2)] {
+            assert_eq!(
+                rotate_position(position, CubeAxis::Y, target_rotations),
+                position
+            );
+        }
+    }
+
+    #[test]
+    fn lag_spike_does_not_overshoot_target() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<LastMoveCompletion>();
+
+        let start_angle = f32::to_radians(80.0);
+        let mut transform =
+            Transform::from_translation(slot_center((2, 0, 2))).with_scale(Vec3::splat(1.0 / 3.0));
+        transform.rotate_around(Vec3::ZERO, Quat::from_rotation_x(start_angle));
+
+        world.spawn((
+            Cube,
+            ActiveCubeRotation {
+                axis: CubeAxis::X,
+                current_angle: start_angle,
+                target_rotations: 1,
+                speed: ROTATION_SPEED,
+                overshoot: 0.0,
+            },
+        ));
+        let cubie = world
+            .spawn((
+                Cubie {
+                    position: (2, 0, 2),
+                    home_position: (2, 0, 2),
+                },
+                transform,
+                BeingDragged {
+                    prev_rotation: Quat::IDENTITY,
+                },
+            ))
+            .id();
+
+        let mut frames = 0;
+        loop {
+            // A multi-second stall on every frame
+            world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(5));
+            world.run_system_once(cubie_rotation_system).unwrap();
+            frames += 1;
+
+            let mut rotations = world.query::<&ActiveCubeRotation>();
+            let Ok(active_rotation) = rotations.single(&world) else {
+                break;
+            };
+            assert!(
+                active_rotation.current_angle <= PI / 2.0 + 1e-5,
+                "overshot to {} rad",
+                active_rotation.current_angle
+            );
+            assert!(frames < 100, "rotation never completed");
+        }
+
+        // The stall is animated like a single slow frame rather than skipping to the end
+        assert!(frames > 1);
+
+        let expected_position = rotate_position((2, 0, 2), CubeAxis::X, 1);
+        assert_eq!(
+            world.get::<Cubie>(cubie).unwrap().position,
+            expected_position
+        );
+
+        let transform = world.get::<Transform>(cubie).unwrap();
+        assert!(
+            transform
+                .translation
+                .abs_diff_eq(slot_center(expected_position), 1e-5)
+        );
+        assert!(
+            transform
+                .rotation
+                .abs_diff_eq(Quat::from_rotation_x(PI / 2.0), 1e-5)
+        );
+        assert!(world.get::<BeingDragged>(cubie).is_none());
+    }
+
+    #[test]
+    fn cubie_mesh_triangles_wind_outward() {
+        use bevy::mesh::VertexAttributeValues;
+
+        for shape in [CubeShape::Standard, CubeShape::Mirror] {
+            for position in [(0, 0, 0), (2, 2, 2), (2, 0, 1), (1, 1, 2)] {
+                let bounds = cubie_bounds(shape, position);
+                let center = (bounds.min + bounds.max) / 2.0;
+                let colors = CubeFaceColors {
+                    pos_x: Some(Color::WHITE),
+                    neg_x: None,
+                    pos_y: Some(Color::WHITE),
+                    neg_y: None,
+                    pos_z: Some(Color::WHITE),
+                    neg_z: None,
+                };
+                let mesh = colored_cube_mesh(colors, &bounds);
+
+                let Some(VertexAttributeValues::Float32x3(positions)) =
+                    mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+                else {
+                    panic!("missing positions");
+                };
+                let Some(VertexAttributeValues::Float32x3(normals)) =
+                    mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+                else {
+                    panic!("missing normals");
+                };
+                let Some(Indices::U32(indices)) = mesh.indices() else {
+                    panic!("missing indices");
+                };
+
+                assert_eq!(indices.len(), 6 * 2 * 3);
+
+                for triangle in indices.chunks_exact(3) {
+                    let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i] as usize]));
+                    let face_normal = (b - a).cross(c - a).normalize();
+
+                    // Every vertex of the triangle carries the same normal as its face
+                    for &index in triangle {
+                        let normal = Vec3::from(normals[index as usize]);
+                        assert!(
+                            face_normal.abs_diff_eq(normal, 1e-5),
+                            "{shape:?} {position:?}: triangle {triangle:?} winds towards \
+                             {face_normal}, but its normal is {normal}"
+                        );
+                    }
+
+                    // And the face points away from the cubie's center
+                    let triangle_center = (a + b + c) / 3.0;
+                    assert!((triangle_center - center).dot(face_normal) > 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flicked_layer_settles_on_a_quarter_turn() {
+        for release_angle in [-2.0, -0.7, -0.1, 0.0, 0.3, 0.78, 0.8, 1.5, 3.0, 6.2] {
+            for angular_velocity in [-40.0, -8.0, -1.0, 0.0, 1.0, 8.0, 40.0] {
+                let (target_rotations, speed) =
+                    release_with_momentum(release_angle, angular_velocity);
+                assert!(speed >= ROTATION_SPEED);
+
+                // Coasting carries the layer at most one quarter turn past where it was let go
+                let coast = angle_to_target(release_angle, target_rotations);
+                assert!(coast.abs() <= 3.0 * PI / 4.0 + 1e-4, "coasted {coast} rad");
+
+                let mut current_angle = release_angle.rem_euclid(2.0 * PI);
+                let mut frames = 0;
+                while !rotation_finished(current_angle, target_rotations) {
+                    current_angle +=
+                        rotation_step(current_angle, target_rotations, speed, FRAME_SECS);
+                    frames += 1;
+                    assert!(frames < 600, "never settled");
+                }
+
+                let quarter_turns = current_angle / (PI / 2.0);
+                assert!((quarter_turns - quarter_turns.round()).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn layer_drag_directions_follow_the_turn_at_extreme_aspect_ratios() {
+        let cubies = test_cubies().collect::<Vec<_>>();
+
+        for (view, size) in [CameraView::HeadOn, CameraView::Corner]
+            .into_iter()
+            .flat_map(|view| {
+                [
+                    UVec2::new(1280, 720),
+                    UVec2::new(3840, 240),
+                    UVec2::new(240, 3840),
+                ]
+                .map(|size| (view, size))
+            })
+        {
+            let cube_transform =
+                GlobalTransform::from(Transform::from_rotation(default_cube_rotation(view)));
+            let camera_transform = GlobalTransform::from(camera_view_transform(view));
+            let camera = test_camera(size);
+            let project = |cube_point: Vec3| {
+                camera
+                    .world_to_viewport(
+                        &camera_transform,
+                        cube_transform.transform_point(cube_point),
+                    )
+                    .unwrap()
+            };
+
+            let mut picks = 0;
+            for step_x in 1..40 {
+                for step_y in 1..40 {
+                    let cursor_position =
+                        size.as_vec2() * Vec2::new(step_x as f32, step_y as f32) / 40.0;
+                    let Ok(pick) = cursor_pick(
+                        cursor_position,
+                        &camera,
+                        &camera_transform,
+                        &cube_transform,
+                        cubies
+                            .iter()
+                            .map(|(cubie, transform, bounds)| (cubie, transform, bounds)),
+                    ) else {
+                        continue;
+                    };
+                    picks += 1;
+
+                    let candidates =
+                        pick_layer_candidates(&pick, &camera, &camera_transform, &cube_transform)
+                            .unwrap();
+
+                    for (axis, _, viewport_dir) in candidates {
+                        // Where the picked point moves on screen when its layer starts turning
+                        let turned = Quat::from_axis_angle(axis.unit(), 1e-2) * pick.point;
+                        let moved = (project(turned) - project(pick.point)).normalize();
+
+                        assert!(
+                            moved.dot(viewport_dir) > 0.999,
+                            "{view:?} {size}: dragging {axis:?} at {cursor_position} goes \
+                             {viewport_dir}, but the layer moves {moved}"
+                        );
+                    }
+                }
+            }
+
+            assert!(
+                picks > 0,
+                "{view:?} {size}: no cubie under any sampled position"
+            );
+        }
+    }
+
+    #[test]
+    fn color_scheme_with_repeated_colors_falls_back_to_default() {
+        use crate::preferences::StickerColor;
+
+        let swapped = ColorScheme {
+            pos_x: StickerColor::Blue,
+            neg_y: StickerColor::White,
+            ..default()
+        };
+        assert_eq!(valid_color_scheme(&swapped), swapped);
+
+        let repeated = ColorScheme {
+            pos_x: StickerColor::Red,
+            ..default()
+        };
+        assert!(!repeated.has_distinct_colors());
+        assert_eq!(valid_color_scheme(&repeated), ColorScheme::default());
+    }
+
+    #[test]
+    fn both_camera_views_show_three_faces_from_the_same_distance() {
+        for (view, shown) in [
+            (
+                CameraView::HeadOn,
+                [CubeFace::PosX, CubeFace::PosY, CubeFace::PosZ],
+            ),
+            (
+                CameraView::Corner,
+                [CubeFace::NegX, CubeFace::PosY, CubeFace::PosZ],
+            ),
+        ] {
+            let camera = camera_view_transform(view);
+            assert!((camera.translation.length() - CAMERA_DISTANCE).abs() < 1e-5);
+
+            let to_camera =
+                (default_cube_rotation(view).inverse() * camera.translation).normalize();
+            let facing = CubeFace::ALL
+                .into_iter()
+                .filter(|face| face.normal().dot(to_camera) > 0.3)
+                .collect::<Vec<_>>();
+            assert_eq!(facing, shown, "{view:?}");
+        }
+    }
+
+    #[test]
+    fn free_rotation_stays_normalized_over_a_long_session() {
+        let mut rotation = default_cube_rotation(CameraView::HeadOn);
+
+        // Tiny, uneven mouse deltas, the worst case for accumulated rounding error
+        for step in 0..20_000 {
+            let delta = Vec2::new(
+                (step % 7) as f32 * 0.13 - 0.4,
+                (step % 5) as f32 * 0.09 - 0.2,
+            );
+            rotation = apply_free_rotation(rotation, Quat::from_rotation_y(delta.x * 0.01), None);
+            rotation = apply_free_rotation(rotation, Quat::from_rotation_x(delta.y * 0.01), None);
+            assert!(
+                (rotation.length() - 1.0).abs() < 1e-5,
+                "drifted to {}",
+                rotation.length()
+            );
+        }
+
+        let transform = Transform::from_rotation(rotation);
+        let scale = transform.compute_affine().to_scale_rotation_translation().0;
+        assert!(scale.abs_diff_eq(Vec3::ONE, 1e-4));
+    }
+
+    #[test]
+    fn detent_fires_when_drag_passes_a_quarter_turn() {
+        assert!(!crosses_quarter_turn(0.1, 1.5));
+        assert!(crosses_quarter_turn(1.5, 1.6));
+        assert!(crosses_quarter_turn(1.6, 1.5));
+        assert!(crosses_quarter_turn(0.05, -0.05));
+        assert!(!crosses_quarter_turn(-0.2, -1.5));
+        assert!(crosses_quarter_turn(3.0, 3.3));
+    }
+
+    #[test]
+    fn simultaneous_layer_moves_never_overlap() {
+        // Two turns requested in the same frame, only the first one starts
+        let mut world = move_input_world(ControlScheme::SelectAndTurn);
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::ArrowRight);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                layer_select::turn_selected_layer_system,
+                layer_select::turn_selected_layer_system,
+            )
+                .chain(),
+        );
+        schedule.run(&mut world);
+
+        let cube = cube_entity(&mut world);
+        assert!(world.get::<ActiveCubeRotation>(cube).is_some());
+        let grabbed = world.query::<&BeingDragged>().iter(&world).count();
+        assert_eq!(grabbed, 8, "grabbed more than one layer"); // A middle layer, around the core
+
+        // A key press in the middle of a drag is ignored
+        let mut world = move_input_world(ControlScheme::SelectAndTurn);
+        let cube = cube_entity(&mut world);
+        world.entity_mut(cube).insert(ActiveDrag {
+            axis: CubeAxis::Y,
+            viewport_origin: Vec2::ZERO,
+            viewport_dir: Vec2::X,
+            current_angle: 0.3,
+            angular_velocity: 0.0,
+        });
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::ArrowRight);
+        let _ = world.run_system_once(layer_select::turn_selected_layer_system);
+        assert!(world.get::<ActiveCubeRotation>(cube).is_none());
+        assert_eq!(world.query::<&BeingDragged>().iter(&world).count(), 0);
+
+        // And so is a click while a turn is animating
+        let mut world = move_input_world(ControlScheme::Drag);
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        world.run_system_once(cubie_drag_init_system).unwrap();
+        let cube = cube_entity(&mut world);
+        assert!(
+            world.get::<PendingDrag>(cube).is_some(),
+            "an idle cube should start a drag"
+        );
+
+        world
+            .entity_mut(cube)
+            .remove::<PendingDrag>()
+            .insert(ActiveCubeRotation {
+                axis: CubeAxis::Y,
+                current_angle: 0.3,
+                target_rotations: 1,
+                speed: ROTATION_SPEED,
+                overshoot: 0.0,
+            });
+        let _ = world.run_system_once(cubie_drag_init_system);
+        assert!(world.get::<PendingDrag>(cube).is_none());
+    }
+
+    #[test]
+    fn left_handed_drags_mirror_horizontal_turns() {
+        for (handedness, drag, turns_forward) in [
+            (Handedness::Right, Vec2::X, true),
+            (Handedness::Left, Vec2::X, false),
+            (Handedness::Right, Vec2::Y, true),
+            (Handedness::Left, Vec2::Y, true),
+        ] {
+            let mut world = move_input_world(ControlScheme::Drag);
+            world.resource_mut::<Preferences>().handedness = handedness;
+            world
+                .resource_mut::<ButtonInput<MouseButton>>()
+                .press(MouseButton::Left);
+
+            let cursor_position = world
+                .query::<&Window>()
+                .single(&world)
+                .unwrap()
+                .cursor_position()
+                .unwrap();
+            let cube = cube_entity(&mut world);
+            world.entity_mut(cube).insert(ActiveDrag {
+                axis: CubeAxis::Y,
+                viewport_origin: cursor_position - drag * 50.0,
+                viewport_dir: drag,
+                current_angle: 0.0,
+                angular_velocity: 0.0,
+            });
+            world.run_system_once(cubie_drag_system).unwrap();
+
+            let current_angle = world.get::<ActiveDrag>(cube).unwrap().current_angle;
+            assert_eq!(
+                current_angle > 0.0,
+                turns_forward,
+                "{handedness:?} drag along {drag} turned the layer by {current_angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn drags_that_dont_turn_report_an_abort() {
+        let aborts = |world: &mut World| {
+            world
+                .resource_mut::<Messages<CubeEvent>>()
+                .drain()
+                .filter(|event| *event == CubeEvent::DragAborted)
+                .count()
+        };
+
+        // A click that never leaves the dead zone
+        let mut world = move_input_world(ControlScheme::Drag);
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        world.run_system_once(cubie_drag_init_system).unwrap();
+        world.run_system_once(cubie_drag_pending_system).unwrap();
+        assert_eq!(aborts(&mut world), 0, "still pressed, not aborted yet");
+
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .release(MouseButton::Left);
+        world.run_system_once(cubie_drag_pending_system).unwrap();
+        assert_eq!(aborts(&mut world), 1);
+
+        // Layers let go short of a quarter turn, and past one
+        for (current_angle, expected_aborts) in [(0.3, 1), (-0.3, 1), (1.2, 0)] {
+            let mut world = move_input_world(ControlScheme::Drag);
+            let cube = cube_entity(&mut world);
+            world.entity_mut(cube).insert(ActiveDrag {
+                axis: CubeAxis::Y,
+                viewport_origin: Vec2::ZERO,
+                viewport_dir: Vec2::X,
+                current_angle,
+                angular_velocity: 0.0,
+            });
+            world.run_system_once(cubie_drag_system).unwrap();
+            assert_eq!(
+                aborts(&mut world),
+                expected_aborts,
+                "released at {current_angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn wide_turns_move_the_middle_layer_with_the_outer_one() {
+        let mut world = move_input_world(ControlScheme::SelectAndTurn);
+        world.resource_mut::<Preferences>().wide_turn_modifier = true;
+        world.insert_resource(layer_select::SelectedLayer(Some(CubiePick {
+            position: (2, 2, 2),
+            face: CubeFace::PosZ,
+            point: Vec3::new(0.3, 0.3, 0.5),
+        })));
+        let mut keys = world.resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::ShiftLeft);
+        keys.press(KeyCode::ArrowRight);
+        world
+            .run_system_once(layer_select::turn_selected_layer_system)
+            .unwrap();
+
+        // The top layer and the middle one, the core isn't a cubie
+        let grabbed = world
+            .query_filtered::<&Cubie, With<BeingDragged>>()
+            .iter(&world)
+            .map(|cubie| cubie.position)
+            .collect::<Vec<_>>();
+        assert_eq!(grabbed.len(), 17);
+        assert!(grabbed.iter().all(|position| position.1 >= 1));
+
+        let cube = cube_entity(&mut world);
+        assert!(world.entity(cube).contains::<WideTurn>());
+
+        // Settled, both layers land on whole slots with no two cubies in the same one
+        let mut rotation = world.get_mut::<ActiveCubeRotation>(cube).unwrap();
+        rotation.current_angle = (PI / 2.0) * rotation.target_rotations as f32;
+        world.run_system_once(cubie_rotation_system).unwrap();
+
+        let mut positions = world
+            .query::<&Cubie>()
+            .iter(&world)
+            .map(|cubie| cubie.position)
+            .collect::<Vec<_>>();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), 26);
+        assert!(!world.entity(cube).contains::<WideTurn>());
+        assert!(
+            world
+                .query::<(&Cubie, &Transform)>()
+                .iter(&world)
+                .all(|(cubie, transform)| transform
+                    .translation
+                    .abs_diff_eq(slot_translation(cubie.position), 1e-5))
+        );
+        let moved = world
+            .query::<&Cubie>()
+            .iter(&world)
+            .filter(|cubie| cubie.position != cubie.home_position)
+            .count();
+        assert_eq!(moved, 16, "every cubie of both layers but the top center");
+    }
+
+    #[test]
+    fn drag_assist_turns_edge_on_faces_towards_the_camera() {
+        let camera_transform = GlobalTransform::from(Transform::from_xyz(0.0, 0.0, 3.0));
+        let pick = |face: CubeFace, point: Vec3| CubiePick {
+            position: (2, 1, 2),
+            face,
+            point,
+        };
+
+        // The front face is already head-on
+        let front = pick(CubeFace::PosZ, Vec3::new(0.3, 0.0, 0.5));
+        assert!(
+            drag_assist_rotation(&front, &camera_transform, &GlobalTransform::IDENTITY).is_none()
+        );
+
+        // The right face is seen edge-on, the assist turns it to face the camera partway
+        let side = pick(CubeFace::PosX, Vec3::new(0.5, 0.0, 0.3));
+        let cube_rotation =
+            drag_assist_rotation(&side, &camera_transform, &GlobalTransform::IDENTITY).unwrap();
+        let cube_transform = GlobalTransform::from(Transform::from_rotation(cube_rotation));
+        let normal = cube_rotation * Vec3::X;
+        let to_camera = (camera_transform.translation()
+            - cube_transform.transform_point(side.point))
+        .normalize();
+        assert!(
+            normal.dot(to_camera) > DRAG_ASSIST_FACING - 0.1,
+            "still facing away at {}",
+            normal.dot(to_camera)
+        );
+        assert!(
+            cube_rotation.angle_between(Quat::IDENTITY) < PI / 4.0,
+            "the assist should only tilt the cube"
+        );
+    }
+
+    #[test]
+    fn assisted_drags_wait_for_the_tilt_to_settle() {
+        let mut world = move_input_world(ControlScheme::Drag);
+        {
+            let mut preferences = world.resource_mut::<Preferences>();
+            preferences.drag_assist = true;
+            preferences.free_rotation = true;
+        }
+
+        // Turned so the front face is seen edge-on, pressed on its right column
+        let cube = cube_entity(&mut world);
+        let cube_transform = Transform::from_rotation(Quat::from_rotation_y(-1.2));
+        world
+            .entity_mut(cube)
+            .insert((cube_transform, GlobalTransform::from(cube_transform)));
+        let (camera, camera_transform) = world
+            .query::<(&Camera, &GlobalTransform)>()
+            .single(&world)
+            .unwrap();
+        let pressed_at = camera
+            .world_to_viewport(
+                camera_transform,
+                cube_transform.transform_point(Vec3::new(0.3, 0.0, 0.5)),
+            )
+            .unwrap();
+        let set_cursor = |world: &mut World, position: Vec2| {
+            world
+                .query::<&mut Window>()
+                .single_mut(world)
+                .unwrap()
+                .set_cursor_position(Some(position));
+        };
+        set_cursor(&mut world, pressed_at);
+
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        world.run_system_once(cubie_drag_init_system).unwrap();
+        let target = world
+            .get::<view_presets::SnappingToView>(cube)
+            .expect("the edge-on face should be tilted towards the camera")
+            .target;
+        assert!(world.entity(cube).contains::<view_presets::AutoAligning>());
+        assert!(world.entity(cube).contains::<DeferredDrag>());
+
+        // Dragged up while the cube is still tilting, nothing is grabbed yet
+        set_cursor(&mut world, pressed_at - Vec2::new(0.0, 40.0));
+        world.run_system_once(deferred_drag_system).unwrap();
+        assert!(!world.entity(cube).contains::<PendingDrag>());
+        assert_eq!(world.query::<&BeingDragged>().iter(&world).count(), 0);
+
+        // Settled, the drag starts from where the face ended up
+        let settled = Transform::from_rotation(target);
+        world
+            .entity_mut(cube)
+            .insert(settled)
+            .remove::<view_presets::SnappingToView>();
+        world.run_system_once(deferred_drag_system).unwrap();
+        world.run_system_once(cubie_drag_pending_system).unwrap();
+        world.run_system_once(cubie_drag_system).unwrap();
+
+        let active_drag = world.get::<ActiveDrag>(cube).unwrap();
+        assert_eq!(active_drag.axis, CubeAxis::X);
+        // Dragging the front up turns the right layer backwards, negatively around X
+        assert!(
+            active_drag.current_angle < 0.0,
+            "turned {}",
+            active_drag.current_angle
+        );
+        let grabbed = world
+            .query_filtered::<&Cubie, With<BeingDragged>>()
+            .iter(&world)
+            .map(|cubie| cubie.position.0)
+            .collect::<Vec<_>>();
+        assert_eq!(grabbed.len(), 9);
+        assert!(grabbed.iter().all(|x| *x == 2));
+    }
+
+    #[test]
+    fn scrolling_turns_the_hovered_face_when_enabled() {
+        use bevy::input::mouse::MouseScrollUnit;
+
+        // Scrolling up turns the front face clockwise as seen from it, down counterclockwise
+        for (scroll_turns, scroll, expected_rotations) in [
+            (true, 1.0, Some(3)),
+            (true, -1.0, Some(1)),
+            (false, 1.0, None),
+        ] {
+            let mut app = fixed_clock_app(FRAME_SECS);
+            add_move_input(app.world_mut(), ControlScheme::Drag);
+            app.world_mut().resource_mut::<Preferences>().scroll_turns = scroll_turns;
+            app.add_message::<MouseWheel>()
+                .add_systems(Update, scroll_turn_system);
+
+            app.world_mut().write_message(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: scroll,
+                window: Entity::PLACEHOLDER,
+            });
+            app.update();
+
+            let cube = cube_entity(app.world_mut());
+            let world = app.world_mut();
+            let rotation = world.get::<ActiveCubeRotation>(cube);
+            assert_eq!(
+                rotation.map(|rotation| (rotation.axis, rotation.target_rotations)),
+                expected_rotations.map(|rotations| (CubeAxis::Z, rotations)),
+                "scrolled {scroll} with scroll turns {scroll_turns}"
+            );
+
+            // The cursor is over the middle of the front face, its whole layer turns
+            let grabbed = world
+                .query_filtered::<&Cubie, With<BeingDragged>>()
+                .iter(world)
+                .map(|cubie| cubie.position.2)
+                .collect::<Vec<_>>();
+            let expected_grabbed = if expected_rotations.is_some() { 9 } else { 0 };
+            assert_eq!(grabbed.len(), expected_grabbed);
+            assert!(grabbed.iter().all(|z| *z == 2));
+        }
+    }
+
+    #[test]
+    fn scrolls_during_a_turn_are_queued_behind_it() {
+        use bevy::input::mouse::MouseScrollUnit;
+        use std::sync::{Arc, Mutex};
+
+        let scroll = |app: &mut App, y: f32| {
+            app.world_mut().write_message(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y,
+                window: Entity::PLACEHOLDER,
+            });
+            app.update();
+        };
+        let turn = |app: &mut App| {
+            let cube = cube_entity(app.world_mut());
+            app.world()
+                .get::<ActiveCubeRotation>(cube)
+                .map(|rotation| rotation.target_rotations)
+        };
+
+        let mut app = fixed_clock_app(FRAME_SECS);
+        add_move_input(app.world_mut(), ControlScheme::Drag);
+        app.world_mut().resource_mut::<Preferences>().scroll_turns = true;
+        app.add_message::<MouseWheel>().add_systems(
+            Update,
+            (
+                scroll_turn_system,
+                cubie_rotation_system,
+                queued_turn_system,
+            )
+                .chain(),
+        );
+
+        // Past the cap, notches are dropped rather than piling up
+        for y in [1.0, -1.0, 1.0, -1.0, 1.0] {
+            scroll(&mut app, y);
+        }
+        assert_eq!(turn(&mut app), Some(3));
+        assert_eq!(
+            app.world()
+                .resource::<QueuedTurns>()
+                .0
+                .iter()
+                .map(|turn| turn.target_rotations)
+                .collect::<Vec<_>>(),
+            [1, 3, 1]
+        );
+
+        let made = Arc::new(Mutex::new(Vec::new()));
+        let sink = made.clone();
+        app.world_mut()
+            .get_resource_or_init::<MoveCallbacks>()
+            .add(move |player_move, _| sink.lock().unwrap().push(player_move.quarter_turns));
+        for _ in 0..1000 {
+            app.update();
+        }
+
+        // Each queued turn ran in order once the one before it settled
+        assert_eq!(*made.lock().unwrap(), [3, 1, 3, 1]);
+        assert!(app.world().resource::<QueuedTurns>().0.is_empty());
+        assert_eq!(turn(&mut app), None);
+
+        // Select and turn has no scroll turns
+        let mut app = fixed_clock_app(FRAME_SECS);
+        add_move_input(app.world_mut(), ControlScheme::SelectAndTurn);
+        app.world_mut().resource_mut::<Preferences>().scroll_turns = true;
+        app.add_message::<MouseWheel>()
+            .add_systems(Update, scroll_turn_system);
+        scroll(&mut app, 1.0);
+        assert_eq!(turn(&mut app), None);
+    }
+
+    #[test]
+    fn confirmed_turns_wait_for_a_click_or_cancel() {
+        for (confirmed, expected_rotations) in [(true, 1), (false, 0)] {
+            let mut world = move_input_world(ControlScheme::Drag);
+            world.resource_mut::<Preferences>().confirm_turns = true;
+            let cube = cube_entity(&mut world);
+            world.entity_mut(cube).insert(ActiveDrag {
+                axis: CubeAxis::Y,
+                viewport_origin: Vec2::ZERO,
+                viewport_dir: Vec2::X,
+                current_angle: 1.2,
+                angular_velocity: 0.0,
+            });
+
+            // Released, the layer is held rather than turned
+            world.run_system_once(cubie_drag_system).unwrap();
+            assert!(world.get::<ActiveCubeRotation>(cube).is_none());
+            assert_eq!(world.get::<PendingTurn>(cube).unwrap().target_rotations, 1);
+
+            // Nothing else can start while it waits
+            world
+                .resource_mut::<ButtonInput<MouseButton>>()
+                .press(MouseButton::Left);
+            let _ = world.run_system_once(cubie_drag_init_system);
+            assert!(world.get::<PendingDrag>(cube).is_none());
+            world
+                .resource_mut::<ButtonInput<MouseButton>>()
+                .release(MouseButton::Left);
+            world.resource_mut::<ButtonInput<MouseButton>>().clear();
+
+            // A click makes the turn, Escape puts the layer back
+            if confirmed {
+                world
+                    .resource_mut::<ButtonInput<MouseButton>>()
+                    .press(MouseButton::Left);
+            } else {
+                world
+                    .resource_mut::<ButtonInput<KeyCode>>()
+                    .press(CANCEL_TURN_KEY);
+            }
+            world.run_system_once(confirm_turn_system).unwrap();
+
+            assert!(world.get::<PendingTurn>(cube).is_none());
+            assert_eq!(
+                world
+                    .get::<ActiveCubeRotation>(cube)
+                    .unwrap()
+                    .target_rotations,
+                expected_rotations
+            );
+        }
+    }
+
+    #[test]
+    fn easy_turns_commit_short_drags() {
+        for (drag_degrees, expected_rotations) in [
+            (0.0, 0),
+            (2.0, 1),
+            (-2.0, 3),
+            (30.0, 1),
+            (-44.0, 3),
+            (60.0, 1),
+            (100.0, 1),
+            (170.0, 2),
+            (-100.0, 3),
+            (358.0, 0), // Dragged all the way around, not a short drag
+        ] {
+            let angle = f32::to_radians(drag_degrees);
+            let target_rotations = assisted_quarter_turns(angle, nearest_quarter_turns(angle));
+            assert_eq!(
+                target_rotations, expected_rotations,
+                "drag released at {drag_degrees} degrees"
+            );
+        }
+    }
+
+    #[test]
+    fn cubies_get_a_colored_face_per_outer_side() {
+        let color_scheme = ColorScheme::default();
+
+        for x in 0..3 {
+            for y in 0..3 {
+                for z in 0..3 {
+                    let position: (u32, u32, u32) = (x, y, z);
+                    let outer_sides = [x, y, z].iter().filter(|&&i| i != 1).count();
+
+                    let colors =
+                        cubie_face_colors(CubeShape::Standard, &color_scheme, default(), position);
+                    let faces = [
+                        (CubeFace::PosX, colors.pos_x),
+                        (CubeFace::NegX, colors.neg_x),
+                        (CubeFace::PosY, colors.pos_y),
+                        (CubeFace::NegY, colors.neg_y),
+                        (CubeFace::PosZ, colors.pos_z),
+                        (CubeFace::NegZ, colors.neg_z),
+                    ];
+
+                    // Three for corners, two for edges, one for centers and none for the core
+                    let colored = faces.iter().filter(|(_, color)| color.is_some()).count();
+                    assert_eq!(colored, outer_sides, "{position:?}");
+
+                    for (face, color) in faces {
+                        if let Some(color) = color {
+                            assert_eq!(color, face_sticker(&color_scheme, face).color());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_cube_has_one_single_color_center_per_face() {
+        let color_scheme = ColorScheme::default();
+
+        let centers = (0..27)
+            .map(|i| (i / 9, i / 3 % 3, i % 3))
+            .filter(|&position| cubie_kind(position) == CubieKind::Center)
+            .collect::<Vec<_>>();
+        assert_eq!(centers.len(), 6);
+
+        // Each is on a different face and carries only that face's color
+        for face in CubeFace::ALL {
+            let on_face = centers
+                .iter()
+                .filter(|&&position| is_outer_face(position, face))
+                .collect::<Vec<_>>();
+            assert_eq!(on_face.len(), 1, "{face:?}");
+
+            let colors =
+                cubie_face_colors(CubeShape::Standard, &color_scheme, default(), *on_face[0]);
+            let colored = [
+                colors.pos_x,
+                colors.neg_x,
+                colors.pos_y,
+                colors.neg_y,
+                colors.pos_z,
+                colors.neg_z,
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+            assert_eq!(
+                colored,
+                [face_sticker(&color_scheme, face).color()],
+                "{face:?}"
+            );
+        }
+
+        assert_eq!(cubie_kind((1, 1, 1)), CubieKind::Core);
+        assert_eq!(cubie_kind((0, 1, 2)), CubieKind::Edge);
+        assert_eq!(cubie_kind((2, 0, 2)), CubieKind::Corner);
+    }
+
+    #[test]
+    fn bouncing_turns_overshoot_then_land_on_target() {
+        let preferences = Preferences {
+            turn_easing: TurnEasing::Bounce,
+            ..default()
+        };
+
+        for (start_angle, target_rotations) in [(0.0, 1), (0.0, 3), (f32::to_radians(80.0), 0)] {
+            let mut world = World::new();
+            world.init_resource::<Time>();
+            world.init_resource::<LastMoveCompletion>();
+
+            world.spawn((
+                Cube,
+                ActiveCubeRotation {
+                    axis: CubeAxis::Y,
+                    current_angle: start_angle,
+                    target_rotations,
+                    speed: ROTATION_SPEED,
+                    overshoot: turn_overshoot(&preferences, start_angle, target_rotations),
+                },
+            ));
+            let cubie = world
+                .spawn((
+                    Cubie {
+                        position: (2, 2, 2),
+                        home_position: (2, 2, 2),
+                    },
+                    Transform::from_translation(slot_translation((2, 2, 2))),
+                    BeingDragged {
+                        prev_rotation: Quat::IDENTITY,
+                    },
+                ))
+                .id();
+
+            let target_angle = target_rotations as f32 * (PI / 2.0);
+            let heading = angle_to_target(start_angle, target_rotations).signum();
+            let mut furthest_past_target = 0.0_f32;
+            for _ in 0..1000 {
+                world
+                    .resource_mut::<Time>()
+                    .advance_by(Duration::from_secs_f32(FRAME_SECS));
+                world.run_system_once(cubie_rotation_system).unwrap();
+
+                let mut rotations = world.query::<&ActiveCubeRotation>();
+                let Ok(active_rotation) = rotations.single(&world) else {
+                    break;
+                };
+                furthest_past_target = furthest_past_target.max(
+                    -angle_to_target(active_rotation.current_angle, target_rotations) * heading,
+                );
+            }
+
+            assert!(
+                world.query::<&ActiveCubeRotation>().iter(&world).count() == 0,
+                "turn to {target_rotations} never settled"
+            );
+            assert!(
+                (furthest_past_target - TURN_OVERSHOOT).abs() < 1e-3,
+                "turn to {target_rotations} swung {furthest_past_target} past its target"
+            );
+
+            // Still lands exactly on the quarter turn
+            let expected = Quat::from_rotation_y(target_angle) * slot_translation((2, 2, 2));
+            let landed = world.get::<Transform>(cubie).unwrap().translation;
+            assert!(landed.abs_diff_eq(expected, 1e-5), "landed at {landed}");
+        }
+    }
+
+    #[test]
+    fn quarter_turn_takes_the_expected_number_of_frames() {
+        let mut app = fixed_clock_app(FRAME_SECS);
+        app.init_resource::<LastMoveCompletion>()
+            .add_systems(Update, cubie_rotation_system);
+
+        let cube = app
+            .world_mut()
+            .spawn((
+                Cube,
+                ActiveCubeRotation {
+                    axis: CubeAxis::Y,
+                    current_angle: 0.0,
+                    target_rotations: 1,
+                    speed: ROTATION_SPEED,
+                    overshoot: 0.0,
+                },
+            ))
+            .id();
+        app.world_mut().spawn((
+            Cubie {
+                position: (2, 2, 2),
+                home_position: (2, 2, 2),
+            },
+            Transform::from_translation(slot_translation((2, 2, 2))),
+            BeingDragged {
+                prev_rotation: Quat::IDENTITY,
+            },
+        ));
+
+        let expected_frames = ((PI / 2.0) / (ROTATION_SPEED * FRAME_SECS)).ceil() as u32;
+
+        let mut frames = 0;
+        while app.world().get::<ActiveCubeRotation>(cube).is_some() {
+            app.update();
+            frames += 1;
+            assert!(
+                frames <= expected_frames,
+                "still turning after {frames} frames"
+            );
+        }
+
+        assert_eq!(frames, expected_frames);
+    }
+
+    #[test]
+    fn input_follows_the_primary_window_when_there_are_several() {
+        let mut world = move_input_world(ControlScheme::Drag);
+
+        // The cursor is over the primary window only
+        world.spawn(Window {
+            title: "Second window".to_string(),
+            ..default()
+        });
+
+        world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+        world.run_system_once(cubie_drag_init_system).unwrap();
+
+        let cube = cube_entity(&mut world);
+        assert!(world.get::<PendingDrag>(cube).is_some());
+    }
+
+    #[test]
+    fn outlines_follow_the_cube_style_and_give_way_to_x_ray() {
+        let mut world = World::new();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<Assets<Image>>();
+        world.insert_resource(CubieMaterial {
+            material: Handle::default(),
+            border_texture: Handle::default(),
+            custom_border_texture: None,
+            outline_material: Handle::default(),
+        });
+        let cubie = world
+            .spawn((
+                Cubie {
+                    position: (2, 2, 2),
+                    home_position: (2, 2, 2),
+                },
+                Mesh3d(Handle::default()),
+            ))
+            .id();
+
+        // Each change spawns or despawns them, the cubie never has more than one
+        for (outlines, x_ray, expected) in [
+            (true, false, 1),
+            (true, false, 1),
+            (true, true, 0),
+            (true, false, 1),
+            (false, false, 0),
+        ] {
+            world.insert_resource(Preferences {
+                cube_style: CubeStyle {
+                    outlines,
+                    x_ray,
+                    ..default()
+                },
+                ..default()
+            });
+            world.run_system_once(apply_cube_style_system).unwrap();
+            let spawned = world
+                .query_filtered::<&ChildOf, With<CubieOutline>>()
+                .iter(&world)
+                .filter(|child_of| child_of.parent() == cubie)
+                .count();
+            assert_eq!(spawned, expected, "outlines {outlines}, x-ray {x_ray}");
+        }
+    }
+
+    #[test]
+    fn leaving_mid_drag_comes_back_to_a_clean_solved_cube() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            bevy::state::app::StatesPlugin,
+            AssetPlugin::default(),
+        ))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Image>()
+        .init_resource::<ClearColor>()
+        .init_resource::<Preferences>()
+        .init_resource::<LastMoveCompletion>()
+        .init_resource::<QueuedTurns>()
+        .init_resource::<exploded::ExplodedView>()
+        .init_state::<GameState>()
+        .add_plugins(layer_select::plugin)
+        .add_systems(OnEnter(GameState::Game), game_setup)
+        .add_systems(OnExit(GameState::Game), game_cleanup);
+
+        let enter = |app: &mut App, state: GameState| {
+            app.world_mut()
+                .resource_mut::<NextState<GameState>>()
+                .set(state);
+            app.update();
+        };
+        enter(&mut app, GameState::Game);
+
+        // Exploded, which holds every move off until it's collapsed again
+        let world = app.world_mut();
+        world
+            .get_resource_or_init::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyE);
+        world
+            .run_system_once(exploded::toggle_exploded_view_system)
+            .unwrap();
+        assert!(world.resource::<exploded::ExplodedView>().blocks_moves());
+
+        // Halfway through a wide drag, with the top layer already moved out of its solved slots
+        let cube = cube_entity(app.world_mut());
+        app.world_mut().entity_mut(cube).insert((
+            ActiveDrag {
+                axis: CubeAxis::Y,
+                viewport_origin: Vec2::ZERO,
+                viewport_dir: Vec2::X,
+                current_angle: 0.7,
+                angular_velocity: 2.0,
+            },
+            WideTurn,
+        ));
+        let world = app.world_mut();
+        let top_layer = world
+            .query::<(Entity, &Cubie)>()
+            .iter(world)
+            .filter(|(_, cubie)| cubie.position.1 == 2)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in top_layer {
+            let mut cubie = world.entity_mut(entity);
+            let mut cubie_data = cubie.get_mut::<Cubie>().unwrap();
+            cubie_data.position = rotate_position(cubie_data.position, CubeAxis::Y, 1);
+            cubie.insert(BeingDragged {
+                prev_rotation: Quat::IDENTITY,
+            });
+        }
+
+        // A move only just made, another one queued and a sticker selected
+        world.resource_mut::<LastMoveCompletion>().0 = Some(12.0);
+        world.resource_mut::<QueuedTurns>().0.push_back(QueuedTurn {
+            axis: CubeAxis::X,
+            layers: 2..=2,
+            target_rotations: 1,
+        });
+        world.resource_mut::<layer_select::SelectedLayer>().0 = Some(CubiePick {
+            position: (1, 1, 2),
+            face: CubeFace::PosZ,
+            point: Vec3::new(0.0, 0.0, 0.5),
+        });
+
+        enter(&mut app, GameState::Menu);
+        enter(&mut app, GameState::Game);
+
+        let world = app.world_mut();
+        let cube = cube_entity(world);
+        assert!(!world.entity(cube).contains::<ActiveDrag>());
+        assert!(!world.entity(cube).contains::<WideTurn>());
+        assert_eq!(world.query::<&Camera3d>().iter(world).count(), 1);
+        assert_eq!(world.query::<&BeingDragged>().iter(world).count(), 0);
+        assert_eq!(world.resource::<LastMoveCompletion>().0, None);
+        assert!(world.resource::<QueuedTurns>().0.is_empty());
+        assert!(world.resource::<layer_select::SelectedLayer>().0.is_none());
+        assert!(!world.resource::<exploded::ExplodedView>().blocks_moves());
+
+        let cubies = world.query::<&Cubie>().iter(world).collect::<Vec<_>>();
+        assert_eq!(cubies.len(), 26);
+        assert!(
+            cubies
+                .iter()
+                .all(|cubie| cubie.position == cubie.home_position)
+        );
+    }
+
+    #[test]
+    fn released_middle_layer_drags_are_named_by_the_convention() {
+        for (convention, expected) in [
+            (SliceConvention::Standard, "M"),
+            (SliceConvention::Mirrored, "M'"),
+        ] {
+            let mut world = move_input_world(ControlScheme::Drag);
+            {
+                let mut preferences = world.resource_mut::<Preferences>();
+                preferences.show_face_labels = true;
+                preferences.slice_convention = convention;
+            }
+            let toast = world
+                .spawn((
+                    hud::Toast(Timer::from_seconds(1.0, TimerMode::Once)),
+                    Text::default(),
+                    Visibility::Hidden,
+                ))
+                .id();
+
+            let middle_layer = world
+                .query::<(Entity, &Cubie)>()
+                .iter(&world)
+                .filter(|(_, cubie)| cubie.position.0 == 1)
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+            for entity in middle_layer {
+                world.entity_mut(entity).insert(BeingDragged {
+                    prev_rotation: Quat::IDENTITY,
+                });
+            }
+
+            // Let go past a counterclockwise quarter turn around X
+            let cube = cube_entity(&mut world);
+            world.entity_mut(cube).insert(ActiveDrag {
+                axis: CubeAxis::X,
+                viewport_origin: Vec2::ZERO,
+                viewport_dir: Vec2::X,
+                current_angle: 1.4,
+                angular_velocity: 0.0,
+            });
+            world.run_system_once(cubie_drag_system).unwrap();
+
+            assert_eq!(
+                world
+                    .get::<ActiveCubeRotation>(cube)
+                    .unwrap()
+                    .target_rotations,
+                1
+            );
+            assert_eq!(world.get::<Text>(toast).unwrap().0, expected);
+        }
+    }
 }
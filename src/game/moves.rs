@@ -0,0 +1,579 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::preferences::Preferences;
+
+use super::{
+    ActiveCubeRotation, ActiveDrag, BeingDragged, Cube, CubeAxis, CubeState, Cubie, DeferredDrag,
+    PendingDrag, PendingTurn, ROTATION_SPEED, exploded::ExplodedView, layer_index, turn_overshoot,
+};
+
+/// A turn of one layer of the cube.
+///
+/// Middle layers are named M, E and S for the X, Y and Z axes, see [`SliceConvention`] for which
+/// way each of them turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub axis: CubeAxis,
+    /// Layer along `axis`, 0 is the one on the negative side and 2 the one on the positive side.
+    pub layer: u32,
+    /// Counterclockwise quarter turns, looking at the cube from the positive end of `axis`.
+    pub quarter_turns: u32,
+}
+
+/// Which outer layer each middle layer turns along with in slice notation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SliceConvention {
+    /// M turns like L, E like D and S like F, the usual convention.
+    #[default]
+    Standard,
+    /// M turns like R, E like U and S like B.
+    Mirrored,
+}
+
+impl SliceConvention {
+    // Whether the middle layer around `axis` turns like the outer layer on its positive side
+    fn follows_positive_side(self, axis: CubeAxis) -> bool {
+        let standard = axis == CubeAxis::Z;
+        match self {
+            SliceConvention::Standard => standard,
+            SliceConvention::Mirrored => !standard,
+        }
+    }
+
+    // Counterclockwise quarter turns seen from the positive end of `axis` to or from clockwise
+    // ones seen from the outer face the slice follows, the conversion is its own inverse
+    fn clockwise_turns(self, axis: CubeAxis, quarter_turns: u32) -> u32 {
+        let quarter_turns = quarter_turns % 4;
+        if self.follows_positive_side(axis) {
+            (4 - quarter_turns) % 4
+        } else {
+            quarter_turns
+        }
+    }
+}
+
+impl Move {
+    /// Middle layer of a 3x3 cube around `axis` turned `clockwise_quarter_turns` clockwise, as
+    /// seen from the outer face it follows under `convention`. `Move::slice(CubeAxis::X, 1,
+    /// SliceConvention::Standard)` is M.
+    pub fn slice(
+        axis: CubeAxis,
+        clockwise_quarter_turns: u32,
+        convention: SliceConvention,
+    ) -> Move {
+        Move {
+            axis,
+            layer: 1,
+            quarter_turns: convention.clockwise_turns(axis, clockwise_quarter_turns),
+        }
+    }
+
+    /// Slice notation for a middle layer move of a 3x3 cube under `convention`, like `M'` or `E2`.
+    /// `None` for the outer layers and for moves that leave the layer where it was.
+    pub fn slice_notation(self, convention: SliceConvention) -> Option<String> {
+        if self.layer != 1 {
+            return None;
+        }
+
+        let letter = match self.axis {
+            CubeAxis::X => "M",
+            CubeAxis::Y => "E",
+            CubeAxis::Z => "S",
+        };
+        match convention.clockwise_turns(self.axis, self.quarter_turns) {
+            1 => Some(letter.to_string()),
+            2 => Some(format!("{letter}2")),
+            3 => Some(format!("{letter}'")),
+            _ => None,
+        }
+    }
+
+    /// The move that undoes this one.
+    pub fn inverse(self) -> Move {
+        Move {
+            quarter_turns: (4 - self.quarter_turns % 4) % 4,
+            ..self
+        }
+    }
+}
+
+/// Every single layer turn of a cube with `size` layers per axis, counted in the slice turn metric
+/// (STM): quarter turns both ways and half turns of each layer, the outer layers and the middle
+/// ones alike each count as one move.
+///
+/// Moves are ordered by axis (X, Y then Z), then by layer from the negative side, then by
+/// quarter turns (1, 2 then 3), so there are `9 * size` of them. A cube with a single layer has
+/// nothing to turn against, so `legal_moves(1)` are the 9 rotations of the whole cube.
+pub fn legal_moves(size: u32) -> Vec<Move> {
+    [CubeAxis::X, CubeAxis::Y, CubeAxis::Z]
+        .into_iter()
+        .flat_map(|axis| {
+            (0..size).flat_map(move |layer| {
+                (1..=3).map(move |quarter_turns| Move {
+                    axis,
+                    layer,
+                    quarter_turns,
+                })
+            })
+        })
+        .collect()
+}
+
+/// [`legal_moves`] without the one that would undo `last_move`, in the same order.
+pub fn legal_moves_after(size: u32, last_move: Move) -> Vec<Move> {
+    let undo = last_move.inverse();
+    legal_moves(size)
+        .into_iter()
+        .filter(|player_move| *player_move != undo)
+        .collect()
+}
+
+/// Closures run every time a layer move completes, with the move and the cube as it left it.
+///
+/// Callbacks run in the order they were added, from the system that finishes turns in the `Update`
+/// schedule, once the turned cubies are in their new slots. Moves started by the player and by
+/// [`try_apply_move`] are both reported, turns that end where they started aren't. A wide turn
+/// reports one move per layer, in the order of [`legal_moves`], each with the cube as it would be
+/// after it. That system can run on any thread, so callbacks have to be `Send + Sync`, and the
+/// frame waits for them to return.
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use ruvik::{GameAppPlugin, MoveCallbacks};
+///
+/// let mut app = App::new();
+/// app.add_plugins(GameAppPlugin::default());
+/// app.world_mut()
+///     .resource_mut::<MoveCallbacks>()
+///     .add(|player_move, cube| println!("{player_move:?} -> {:x}", cube.hash()));
+/// app.run();
+/// ```
+#[derive(Resource, Default)]
+pub struct MoveCallbacks(Vec<MoveCallback>);
+
+type MoveCallback = Box<dyn FnMut(Move, &CubeState) + Send + Sync>;
+
+impl MoveCallbacks {
+    /// Adds a callback, run after the ones already added.
+    pub fn add(&mut self, callback: impl FnMut(Move, &CubeState) + Send + Sync + 'static) {
+        self.0.push(Box::new(callback));
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // `moves` were made together and left the cube as `cube`, each callback sees them one by one
+    pub(super) fn run(&mut self, moves: &[Move], cube: CubeState) {
+        let mut states = vec![cube];
+        for later_move in moves.iter().skip(1).rev() {
+            let mut before = states.last().unwrap().clone();
+            before.apply_move(later_move.inverse()).unwrap();
+            states.push(before);
+        }
+        states.reverse();
+
+        for (player_move, state) in moves.iter().zip(&states) {
+            for callback in &mut self.0 {
+                callback(*player_move, state);
+            }
+        }
+    }
+}
+
+/// Why [`try_apply_move`] couldn't start a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// There is no cube to turn, the game isn't running.
+    NoCube,
+    /// A layer is being dragged or is still turning, moves can't overlap.
+    MoveInProgress,
+    /// The exploded view is open, layers only turn once the cube is collapsed.
+    CubeExploded,
+    /// `layer` is past the last layer of the cube.
+    InvalidLayer(u32),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::NoCube => f.write_str("there is no cube to turn"),
+            MoveError::MoveInProgress => f.write_str("another move is in progress"),
+            MoveError::CubeExploded => f.write_str("the cube is in the exploded view"),
+            MoveError::InvalidLayer(layer) => write!(f, "the cube has no layer {layer}"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Starts turning a layer the same way a drag released on a quarter turn would, so the move is
+/// animated and counts like any other. Whole turns are accepted and leave the cube as it is.
+pub fn try_apply_move(world: &mut World, player_move: Move) -> Result<(), MoveError> {
+    if player_move.layer > 2 {
+        return Err(MoveError::InvalidLayer(player_move.layer));
+    }
+
+    let cube = world
+        .query_filtered::<Entity, With<Cube>>()
+        .single(world)
+        .map_err(|_| MoveError::NoCube)?;
+
+    let cube_entity = world.entity(cube);
+    if cube_entity.contains::<ActiveDrag>()
+        || cube_entity.contains::<DeferredDrag>()
+        || cube_entity.contains::<PendingDrag>()
+        || cube_entity.contains::<PendingTurn>()
+        || cube_entity.contains::<ActiveCubeRotation>()
+    {
+        return Err(MoveError::MoveInProgress);
+    }
+
+    if world
+        .get_resource::<ExplodedView>()
+        .is_some_and(ExplodedView::blocks_moves)
+    {
+        return Err(MoveError::CubeExploded);
+    }
+
+    let target_rotations = player_move.quarter_turns % 4;
+    if target_rotations == 0 {
+        return Ok(());
+    }
+
+    let layer_cubies = world
+        .query::<(Entity, &Cubie, &Transform)>()
+        .iter(world)
+        .filter(|(_, cubie, _)| layer_index(cubie.position, player_move.axis) == player_move.layer)
+        .map(|(entity, _, transform)| (entity, transform.rotation))
+        .collect::<Vec<_>>();
+
+    for (cubie_entity, prev_rotation) in layer_cubies {
+        world
+            .entity_mut(cubie_entity)
+            .insert(BeingDragged { prev_rotation });
+    }
+
+    let overshoot = world
+        .get_resource::<Preferences>()
+        .map_or(0.0, |preferences| {
+            turn_overshoot(preferences, 0.0, target_rotations)
+        });
+    world.entity_mut(cube).insert(ActiveCubeRotation {
+        axis: player_move.axis,
+        current_angle: 0.0,
+        target_rotations,
+        speed: ROTATION_SPEED,
+        overshoot,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        CubeFace, LastMoveCompletion, cubie_rotation_system, rotate_position, slot_translation,
+        test_support::{FRAME_SECS, cube_entity, fixed_clock_app, spawn_test_cube},
+    };
+
+    #[test]
+    fn legal_moves_cover_every_layer_turn_once() {
+        for size in 1..=4 {
+            let moves = legal_moves(size);
+            assert_eq!(moves.len(), 9 * size as usize);
+
+            for (i, player_move) in moves.iter().enumerate() {
+                assert!(
+                    !moves[i + 1..].contains(player_move),
+                    "{player_move:?} repeats"
+                );
+            }
+
+            // Every one of them turns the cube and can be undone
+            for player_move in &moves {
+                let mut cube = CubeState::solved(size);
+                cube.apply_move(*player_move).unwrap();
+                assert_ne!(cube, CubeState::solved(size), "{player_move:?}");
+                cube.apply_move(player_move.inverse()).unwrap();
+                assert_eq!(cube, CubeState::solved(size), "{player_move:?}");
+            }
+
+            for last_move in &moves {
+                let after = legal_moves_after(size, *last_move);
+                assert_eq!(after.len(), moves.len() - 1);
+                assert!(!after.contains(&last_move.inverse()));
+            }
+        }
+
+        assert_eq!(
+            legal_moves(3)[..4],
+            [
+                Move {
+                    axis: CubeAxis::X,
+                    layer: 0,
+                    quarter_turns: 1
+                },
+                Move {
+                    axis: CubeAxis::X,
+                    layer: 0,
+                    quarter_turns: 2
+                },
+                Move {
+                    axis: CubeAxis::X,
+                    layer: 0,
+                    quarter_turns: 3
+                },
+                Move {
+                    axis: CubeAxis::X,
+                    layer: 1,
+                    quarter_turns: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn slice_moves_follow_the_chosen_convention() {
+        let solved = CubeState::solved(3);
+
+        // M takes the front center down like L, or up like R. E takes it right like D, or left like
+        // U. S takes the top center right like F, or left like B.
+        for (convention, name, axis, from, to) in [
+            (
+                SliceConvention::Standard,
+                "M",
+                CubeAxis::X,
+                CubeFace::PosZ,
+                CubeFace::NegY,
+            ),
+            (
+                SliceConvention::Mirrored,
+                "M",
+                CubeAxis::X,
+                CubeFace::PosZ,
+                CubeFace::PosY,
+            ),
+            (
+                SliceConvention::Standard,
+                "E",
+                CubeAxis::Y,
+                CubeFace::PosZ,
+                CubeFace::PosX,
+            ),
+            (
+                SliceConvention::Mirrored,
+                "E",
+                CubeAxis::Y,
+                CubeFace::PosZ,
+                CubeFace::NegX,
+            ),
+            (
+                SliceConvention::Standard,
+                "S",
+                CubeAxis::Z,
+                CubeFace::PosY,
+                CubeFace::PosX,
+            ),
+            (
+                SliceConvention::Mirrored,
+                "S",
+                CubeAxis::Z,
+                CubeFace::PosY,
+                CubeFace::NegX,
+            ),
+        ] {
+            let slice_move = Move::slice(axis, 1, convention);
+            assert_eq!(slice_move.layer, 1);
+            assert_eq!(slice_move.slice_notation(convention).as_deref(), Some(name));
+            assert_eq!(
+                Move::slice(axis, 3, convention)
+                    .slice_notation(convention)
+                    .as_deref(),
+                Some(format!("{name}'").as_str())
+            );
+            assert_eq!(slice_move.inverse(), Move::slice(axis, 3, convention));
+
+            let mut cube = solved.clone();
+            cube.apply_move(slice_move).unwrap();
+            assert_eq!(
+                cube.facelet(to, 1, 1),
+                solved.facelet(from, 1, 1),
+                "{name} under {convention:?} is {slice_move:?}"
+            );
+
+            // The two faces the slice turns between are left as they were
+            let ends = match axis {
+                CubeAxis::X => [CubeFace::NegX, CubeFace::PosX],
+                CubeAxis::Y => [CubeFace::NegY, CubeFace::PosY],
+                CubeAxis::Z => [CubeFace::NegZ, CubeFace::PosZ],
+            };
+            for face in ends {
+                for (u, v) in (0..3).flat_map(|u| (0..3).map(move |v| (u, v))) {
+                    assert_eq!(
+                        cube.facelet(face, u, v),
+                        solved.facelet(face, u, v),
+                        "{name}"
+                    );
+                }
+            }
+        }
+
+        // The usual convention is the one the Move docs always described
+        assert_eq!(
+            Move::slice(CubeAxis::Z, 1, SliceConvention::Standard),
+            Move {
+                axis: CubeAxis::Z,
+                layer: 1,
+                quarter_turns: 3
+            }
+        );
+        let outer = Move {
+            axis: CubeAxis::X,
+            layer: 2,
+            quarter_turns: 1,
+        };
+        assert_eq!(outer.slice_notation(SliceConvention::Standard), None);
+    }
+
+    #[test]
+    fn programmatic_moves_report_why_they_cannot_start() {
+        let mut app = fixed_clock_app(FRAME_SECS);
+        app.init_resource::<LastMoveCompletion>()
+            .add_systems(Update, cubie_rotation_system);
+
+        let turn_top = Move {
+            axis: CubeAxis::Y,
+            layer: 2,
+            quarter_turns: 1,
+        };
+        assert_eq!(
+            try_apply_move(app.world_mut(), turn_top),
+            Err(MoveError::NoCube)
+        );
+
+        app.world_mut().spawn(Cube);
+        let corner = app
+            .world_mut()
+            .spawn((
+                Cubie {
+                    position: (2, 2, 2),
+                    home_position: (2, 2, 2),
+                },
+                Transform::from_translation(slot_translation((2, 2, 2))),
+            ))
+            .id();
+
+        assert_eq!(
+            try_apply_move(
+                app.world_mut(),
+                Move {
+                    layer: 3,
+                    ..turn_top
+                }
+            ),
+            Err(MoveError::InvalidLayer(3))
+        );
+
+        assert_eq!(try_apply_move(app.world_mut(), turn_top), Ok(()));
+        assert_eq!(
+            try_apply_move(app.world_mut(), turn_top),
+            Err(MoveError::MoveInProgress)
+        );
+
+        for _ in 0..100 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world().get::<Cubie>(corner).unwrap().position,
+            rotate_position((2, 2, 2), CubeAxis::Y, 1)
+        );
+        assert_eq!(try_apply_move(app.world_mut(), turn_top), Ok(()));
+    }
+
+    #[test]
+    fn completed_moves_reach_the_callbacks() {
+        use std::sync::{Arc, Mutex};
+
+        let mut app = fixed_clock_app(FRAME_SECS);
+        app.init_resource::<LastMoveCompletion>()
+            .init_resource::<MoveCallbacks>()
+            .add_systems(Update, cubie_rotation_system);
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = reported.clone();
+        app.world_mut()
+            .resource_mut::<MoveCallbacks>()
+            .add(move |player_move, cube| sink.lock().unwrap().push((player_move, cube.clone())));
+
+        spawn_test_cube(app.world_mut());
+
+        let turn_top = Move {
+            axis: CubeAxis::Y,
+            layer: 2,
+            quarter_turns: 1,
+        };
+        let turn_right = Move {
+            axis: CubeAxis::X,
+            layer: 2,
+            quarter_turns: 3,
+        };
+        let mut expected = CubeState::solved(3);
+        for player_move in [turn_top, turn_right] {
+            try_apply_move(app.world_mut(), player_move).unwrap();
+            for _ in 0..100 {
+                app.update();
+            }
+
+            expected.apply_move(player_move).unwrap();
+            assert_eq!(
+                reported.lock().unwrap().pop(),
+                Some((player_move, expected.clone()))
+            );
+        }
+
+        // A wide turn is a move of each layer, each with the cube after it
+        let cube = cube_entity(app.world_mut());
+        let world = app.world_mut();
+        let wide_layers = world
+            .query::<(Entity, &Cubie, &Transform)>()
+            .iter(world)
+            .filter(|(_, cubie, _)| cubie.position.2 >= 1)
+            .map(|(entity, _, transform)| (entity, transform.rotation))
+            .collect::<Vec<_>>();
+        for (entity, prev_rotation) in wide_layers {
+            world
+                .entity_mut(entity)
+                .insert(BeingDragged { prev_rotation });
+        }
+        world.entity_mut(cube).insert(ActiveCubeRotation {
+            axis: CubeAxis::Z,
+            current_angle: 0.0,
+            target_rotations: 2,
+            speed: ROTATION_SPEED,
+            overshoot: 0.0,
+        });
+        for _ in 0..100 {
+            app.update();
+        }
+
+        let half_turn = |layer| Move {
+            axis: CubeAxis::Z,
+            layer,
+            quarter_turns: 2,
+        };
+        let mut middle_done = expected.clone();
+        middle_done.apply_move(half_turn(1)).unwrap();
+        let mut both_done = middle_done.clone();
+        both_done.apply_move(half_turn(2)).unwrap();
+        assert_eq!(
+            *reported.lock().unwrap(),
+            [(half_turn(1), middle_done), (half_turn(2), both_done)]
+        );
+    }
+}
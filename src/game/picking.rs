@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use super::{CubeFace, Cubie, CubieBounds};
+
+pub(super) struct CubiePick {
+    pub(super) position: (u32, u32, u32),
+    pub(super) face: CubeFace,
+    pub(super) point: Vec3, // In the cube's local space
+}
+
+// Casts a ray given in the cube's local space against every cubie box and returns the closest hit.
+// The direction doesn't need to be normalized.
+pub(super) fn pick_cubie<'a>(
+    origin: Vec3,
+    direction: Vec3,
+    cubies: impl IntoIterator<Item = (&'a Cubie, &'a Transform, &'a CubieBounds)>,
+) -> Option<CubiePick> {
+    let mut closest: Option<(f32, CubiePick)> = None;
+
+    for (cubie, transform, bounds) in cubies {
+        let inv = transform.compute_affine().inverse();
+
+        let Some((t, local_normal)) = ray_box_intersection(
+            inv.transform_point3(origin),
+            inv.transform_vector3(direction),
+            bounds.min,
+            bounds.max,
+        ) else {
+            continue;
+        };
+
+        if closest
+            .as_ref()
+            .is_some_and(|(closest_t, _)| *closest_t <= t)
+        {
+            continue;
+        }
+
+        closest = Some((
+            t,
+            CubiePick {
+                position: cubie.position,
+                face: CubeFace::from_normal(transform.rotation * local_normal),
+                point: origin + direction * t,
+            },
+        ));
+    }
+
+    closest.map(|(_, pick)| pick)
+}
+
+// Slab test, returns the entry distance and the outward normal of the face the ray enters through
+fn ray_box_intersection(
+    origin: Vec3,
+    direction: Vec3,
+    min: Vec3,
+    max: Vec3,
+) -> Option<(f32, Vec3)> {
+    let inv_dir = 1.0 / direction;
+
+    let t1 = (min - origin) * inv_dir;
+    let t2 = (max - origin) * inv_dir;
+
+    let t_min = t1.min(t2);
+    let t_max = t1.max(t2);
+
+    let t_enter = t_min.max_element();
+    let t_exit = t_max.min_element();
+
+    if t_enter > t_exit || t_exit <= 0.0 {
+        return None;
+    }
+
+    let normal = if t_enter == t_min.x {
+        Vec3::new(-direction.x.signum(), 0.0, 0.0)
+    } else if t_enter == t_min.y {
+        Vec3::new(0.0, -direction.y.signum(), 0.0)
+    } else {
+        Vec3::new(0.0, 0.0, -direction.z.signum())
+    };
+
+    Some((t_enter, normal))
+}
@@ -0,0 +1,146 @@
+// Fixtures shared by the game's unit tests, and by the benchmarks when they're built
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::preferences::CubeShape;
+
+use super::{Cube, Cubie, CubieBounds, CubieKind, cubie_bounds, cubie_kind, slot_translation};
+
+// The rest is only used by the unit tests
+#[cfg(test)]
+use bevy::window::PrimaryWindow;
+
+#[cfg(test)]
+use crate::preferences::{ControlScheme, Preferences};
+
+#[cfg(test)]
+use super::{
+    CubeEvent, CubeFace, LastMoveCompletion, QueuedTurns, debug, exploded, layer_select,
+    picking::CubiePick,
+};
+
+#[cfg(test)]
+pub(super) const FRAME_SECS: f32 = 1.0 / 60.0;
+
+// App on a fixed clock: every update() advances its Time by exactly `frame_secs` whatever the wall
+// clock does, so animation tests can count frames. Add the systems under test to Update, spawn
+// what they work on, then call update() once per frame. Nothing else is added, not even
+// MinimalPlugins, so a system that needs a resource the test didn't insert fails loudly.
+pub(super) fn fixed_clock_app(frame_secs: f32) -> App {
+    let mut app = App::new();
+    app.add_plugins(bevy::time::TimePlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            frame_secs,
+        )));
+
+    // The first update only starts the clock
+    app.update();
+    app
+}
+
+// The 26 cubies of a solved standard cube in their home slots, laid out like game_setup does
+pub(super) fn test_cubies() -> impl Iterator<Item = (Cubie, Transform, CubieBounds)> {
+    (0..27)
+        .map(|i| (i / 9, i / 3 % 3, i % 3))
+        .filter(|&position| cubie_kind(position) != CubieKind::Core)
+        .map(|position| {
+            (
+                Cubie {
+                    position,
+                    home_position: position,
+                },
+                Transform {
+                    translation: slot_translation(position),
+                    scale: Vec3::splat(1.0 / 3.0),
+                    ..default()
+                },
+                cubie_bounds(CubeShape::Standard, position),
+            )
+        })
+}
+
+// A cube root at the origin with the cubies of test_cubies, with none of the meshes or materials
+// game_setup adds. Returns the cube.
+pub(super) fn spawn_test_cube(world: &mut World) -> Entity {
+    let cube = world
+        .spawn((Cube, Transform::IDENTITY, GlobalTransform::IDENTITY))
+        .id();
+    for cubie in test_cubies() {
+        world.spawn(cubie);
+    }
+    cube
+}
+
+#[cfg(test)]
+pub(super) fn test_camera(size: UVec2) -> Camera {
+    use bevy::camera::CameraProjection;
+
+    let projection = PerspectiveProjection {
+        aspect_ratio: size.x as f32 / size.y as f32,
+        ..default()
+    };
+
+    Camera {
+        computed: bevy::camera::ComputedCameraValues {
+            clip_from_view: projection.get_clip_from_view(),
+            target_info: Some(bevy::camera::RenderTargetInfo {
+                physical_size: size,
+                scale_factor: 1.0,
+            }),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+// Cube facing the camera head-on with every move input wired up, the front center sticker
+// selected and the cursor right over it
+#[cfg(test)]
+pub(super) fn move_input_world(control_scheme: ControlScheme) -> World {
+    let mut world = World::new();
+    add_move_input(&mut world, control_scheme);
+    world
+}
+
+// Resources and entities the move systems run against, a full cube in front of the camera
+#[cfg(test)]
+pub(super) fn add_move_input(world: &mut World, control_scheme: ControlScheme) {
+    world.init_resource::<Time>();
+    world.init_resource::<LastMoveCompletion>();
+    world.init_resource::<QueuedTurns>();
+    world.init_resource::<exploded::ExplodedView>();
+    world.init_resource::<debug::DebugOptions>();
+    world.init_resource::<ButtonInput<KeyCode>>();
+    world.init_resource::<ButtonInput<MouseButton>>();
+    world.init_resource::<Messages<CubeEvent>>();
+    world.insert_resource(Preferences {
+        control_scheme,
+        ..default()
+    });
+    world.insert_resource(layer_select::SelectedLayer(Some(CubiePick {
+        position: (1, 1, 2),
+        face: CubeFace::PosZ,
+        point: Vec3::new(0.0, 0.0, 0.5),
+    })));
+
+    let size = UVec2::new(1280, 720);
+    let mut window = Window::default();
+    window.set_cursor_position(Some(size.as_vec2() / 2.0));
+    world.spawn((window, PrimaryWindow));
+    world.spawn((
+        test_camera(size),
+        GlobalTransform::from(Transform::from_xyz(0.0, 0.0, 3.0)),
+    ));
+
+    spawn_test_cube(world);
+}
+
+#[cfg(test)]
+pub(super) fn cube_entity(world: &mut World) -> Entity {
+    world
+        .query_filtered::<Entity, With<Cube>>()
+        .single(world)
+        .unwrap()
+}
@@ -0,0 +1,65 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::preferences::{ControlScheme, Preferences};
+
+use super::{
+    Cubie, CubieBounds, IdleCube, cursor_pick, exploded::ExplodedView, pick_layer_candidates,
+};
+
+const TURN_HINT_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.8);
+const TURN_HINT_LENGTH: f32 = 0.12; // In cube units, from the hovered point to each arrow tip
+const TURN_HINT_OFFSET: f32 = 0.01; // Lifts the arrows off the face to avoid z-fighting
+
+// Arrows along the two ways a drag from the hovered sticker can turn, shown until the press
+pub(super) fn draw_turn_hints_system(
+    preferences: Res<Preferences>,
+    exploded_view: Res<ExplodedView>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    cube_transform: Single<&GlobalTransform, IdleCube>,
+    cubies: Query<(&Cubie, &Transform, &CubieBounds)>,
+    mut gizmos: Gizmos,
+) {
+    if !preferences.turn_hints
+        || preferences.control_scheme != ControlScheme::Drag
+        || exploded_view.blocks_moves()
+    {
+        return;
+    }
+
+    let Some(cursor_position) = window.cursor_position() else {
+        return;
+    };
+
+    let (camera, camera_transform) = *camera;
+
+    let Ok(pick) = cursor_pick(
+        cursor_position,
+        camera,
+        camera_transform,
+        *cube_transform,
+        cubies,
+    ) else {
+        return;
+    };
+
+    // Only hint at turns a drag could actually start
+    let Ok(candidates) = pick_layer_candidates(&pick, camera, camera_transform, *cube_transform)
+    else {
+        return;
+    };
+
+    let normal = pick.face.normal();
+    let origin = pick.point + normal * TURN_HINT_OFFSET;
+
+    for (axis, _, _) in candidates {
+        let tangent = axis.unit().cross(normal) * TURN_HINT_LENGTH;
+        for tip in [origin + tangent, origin - tangent] {
+            gizmos.arrow(
+                cube_transform.transform_point(origin),
+                cube_transform.transform_point(tip),
+                TURN_HINT_COLOR,
+            );
+        }
+    }
+}
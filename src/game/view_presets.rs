@@ -0,0 +1,157 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::preferences::{CameraView, Preferences};
+
+use super::{Cube, CubeFace, camera_view_transform, default_cube_rotation};
+
+#[derive(Debug, Clone, Copy)]
+enum ViewPreset {
+    Front,  // Front face head-on
+    Corner, // The three quarter view the game starts with
+    Top,    // Looking down on the top face
+}
+
+impl ViewPreset {
+    // Front and top face the camera wherever the view puts it
+    fn rotation(self, view: CameraView) -> Quat {
+        let camera_rotation = camera_view_transform(view).rotation;
+        match self {
+            ViewPreset::Front => camera_rotation,
+            ViewPreset::Corner => default_cube_rotation(view),
+            ViewPreset::Top => camera_rotation * Quat::from_rotation_x(PI / 2.0),
+        }
+    }
+}
+
+// Cube turning towards a view preset, free rotation is ignored until it gets there
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(super) struct SnappingToView {
+    pub(super) target: Quat,
+}
+
+// Snap started by auto-align or drag assist rather than a preset. Pressing on a layer cancels it,
+// so the drag starts on a cube that holds still.
+#[derive(Component)]
+#[component(storage = "SparseSet")]
+pub(super) struct AutoAligning;
+
+const VIEW_PRESET_KEYS: [(KeyCode, ViewPreset); 3] = [
+    (KeyCode::Digit1, ViewPreset::Front),
+    (KeyCode::Digit2, ViewPreset::Corner),
+    (KeyCode::Digit3, ViewPreset::Top),
+];
+
+const VIEW_SNAP_SPEED: f32 = 2.0 * PI; // Radians per second
+
+pub(super) fn view_preset_keys_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    preferences: Res<Preferences>,
+    cube: Single<Entity, With<Cube>>,
+) {
+    if !preferences.free_rotation {
+        return;
+    }
+
+    if let Some((_, preset)) = VIEW_PRESET_KEYS
+        .iter()
+        .find(|(key, _)| keys.just_pressed(*key))
+    {
+        commands
+            .entity(*cube)
+            .insert(SnappingToView {
+                target: preset.rotation(preferences.camera_view),
+            })
+            .remove::<AutoAligning>();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn auto_align_system(
+    mut commands: Commands,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    preferences: Res<Preferences>,
+    camera_transform: Single<&GlobalTransform, With<Camera>>,
+    cube: Single<(Entity, &Transform), (With<Cube>, Without<SnappingToView>)>,
+) {
+    if !preferences.auto_align
+        || !preferences.free_rotation
+        || !mouse_buttons.just_released(MouseButton::Right)
+    {
+        return;
+    }
+
+    let (cube_entity, cube_transform) = *cube;
+    let to_camera = camera_transform.translation() - cube_transform.translation;
+
+    if let Some(target) = squared_up_rotation(cube_transform.rotation, to_camera) {
+        commands
+            .entity(cube_entity)
+            .insert((SnappingToView { target }, AutoAligning));
+    }
+}
+
+// Smallest turn of the cube that points its face nearest `to_camera` straight at the camera
+pub(super) fn squared_up_rotation(rotation: Quat, to_camera: Vec3) -> Option<Quat> {
+    let to_camera = to_camera.try_normalize()?;
+    let normal = CubeFace::ALL
+        .into_iter()
+        .map(|face| rotation * face.normal())
+        .max_by(|a, b| a.dot(to_camera).total_cmp(&b.dot(to_camera)))?;
+
+    Some((Quat::from_rotation_arc(normal, to_camera) * rotation).normalize())
+}
+
+pub(super) fn snap_to_view_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    cube: Single<(Entity, &mut Transform, &SnappingToView), With<Cube>>,
+) {
+    let (cube_entity, mut cube_transform, snapping) = cube.into_inner();
+
+    let remaining = cube_transform.rotation.angle_between(snapping.target);
+    let step = VIEW_SNAP_SPEED * time.delta_secs();
+
+    if remaining <= step {
+        cube_transform.rotation = snapping.target;
+        commands
+            .entity(cube_entity)
+            .remove::<(SnappingToView, AutoAligning)>();
+    } else {
+        cube_transform.rotation = cube_transform
+            .rotation
+            .slerp(snapping.target, step / remaining)
+            .normalize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_align_squares_up_the_face_nearest_the_camera() {
+        let to_camera = Vec3::new(0.0, 0.0, 3.0);
+
+        for rotation in [
+            Quat::from_euler(EulerRot::XYZ, 0.2, -0.3, 0.1),
+            Quat::from_euler(EulerRot::XYZ, 0.1, 1.4, -0.2), // The right face is nearer now
+            default_cube_rotation(CameraView::HeadOn),
+        ] {
+            let target = squared_up_rotation(rotation, to_camera).unwrap();
+
+            let facing = CubeFace::ALL
+                .into_iter()
+                .map(|face| (target * face.normal()).dot(to_camera.normalize()))
+                .fold(f32::MIN, f32::max);
+            assert!((facing - 1.0).abs() < 1e-5, "{rotation}: facing {facing}");
+
+            // The nearest face is at worst as far off as when looking straight at a corner
+            let corner_angle = (1.0 / 3.0_f32.sqrt()).acos();
+            assert!(target.angle_between(rotation) <= corner_angle + 1e-3);
+        }
+    }
+}
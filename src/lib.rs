@@ -1,8 +1,19 @@
 mod game;
 mod main_menu;
+mod preferences;
+mod transition;
+mod window;
 
 use bevy::prelude::*;
 
+pub use game::{
+    CubeAxis, CubeEvent, CubeFace, CubeState, Move, MoveCallbacks, MoveError, SliceConvention,
+    legal_moves, legal_moves_after, try_apply_move,
+};
+
+#[cfg(feature = "bench")]
+pub use game::bench_support;
+
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 enum GameState {
     #[default]
@@ -13,14 +24,46 @@ enum GameState {
 #[derive(Resource)]
 pub struct MainFont(Handle<Font>);
 
-pub struct GameAppPlugin;
+/// The whole game, window included.
+pub struct GameAppPlugin {
+    /// Title of the game's window.
+    pub title: String,
+    /// Window icon, as a path under the assets folder. The platform's default icon is kept when
+    /// it's `None` or can't be loaded.
+    pub icon: Option<String>,
+}
+
+impl Default for GameAppPlugin {
+    fn default() -> Self {
+        Self {
+            title: "Ruvik".to_string(),
+            icon: Some("textures/icon.png".to_string()),
+        }
+    }
+}
 
 impl Plugin for GameAppPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(DefaultPlugins)
-            .init_state::<GameState>()
-            .add_systems(OnEnter(GameState::Menu), setup)
-            .add_plugins((main_menu::plugin, game::plugin));
+        if let Some(icon) = &self.icon {
+            app.insert_resource(window::WindowIcon::new(icon));
+        }
+
+        app.add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: self.title.clone(),
+                ..default()
+            }),
+            ..default()
+        }))
+        .init_state::<GameState>()
+        .add_systems(OnEnter(GameState::Menu), setup)
+        .add_plugins((
+            preferences::plugin,
+            transition::plugin,
+            main_menu::plugin,
+            game::plugin,
+            window::plugin,
+        ));
     }
 }
 
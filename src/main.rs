@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
 fn main() {
-    App::new().add_plugins(ruvik::GameAppPlugin).run();
+    App::new()
+        .add_plugins(ruvik::GameAppPlugin::default())
+        .run();
 }
@@ -1,7 +1,14 @@
 use super::*;
 
+use crate::game::SliceConvention;
+use crate::preferences::{
+    CameraView, ColorScheme, ControlScheme, CubeRotationMode, Handedness, Preferences,
+    StickerColor, TurnEasing,
+};
+
 use bevy::{
     color::palettes::css::{BLACK, WHITE},
+    input::mouse::{MouseScrollUnit, MouseWheel},
     prelude::*,
 };
 
@@ -10,6 +17,10 @@ use bevy::{
 enum MenuState {
     #[default]
     Main,
+    Settings,
+    ConfirmReset,
+    About,
+    Colors,
 }
 
 #[derive(Component)]
@@ -23,12 +34,91 @@ const HOVERED_BUTTON: Color = Color::Srgba(WHITE);
 const HOVERED_PRESSED_BUTTON: Color = Color::Srgba(WHITE);
 const PRESSED_BUTTON: Color = Color::Srgba(WHITE);
 
+// Option picked with the keyboard or a gamepad, activated like a click
 #[derive(Component)]
 struct SelectedOption;
 
+// Scrolls once the settings outgrow the window
 #[derive(Component)]
+struct SettingsGrid;
+
+const SETTINGS_SCROLL_LINE: f32 = 66.0; // One row of settings per wheel notch
+
+const NEXT_OPTION_KEYS: [KeyCode; 2] = [KeyCode::ArrowDown, KeyCode::ArrowRight];
+const PREVIOUS_OPTION_KEYS: [KeyCode; 2] = [KeyCode::ArrowUp, KeyCode::ArrowLeft];
+const ACTIVATE_OPTION_KEYS: [KeyCode; 3] = [KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Space];
+const BACK_KEY: KeyCode = KeyCode::Escape;
+const TAB_KEY: KeyCode = KeyCode::Tab;
+const SHIFT_KEYS: [KeyCode; 2] = [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+
+const NEXT_OPTION_BUTTONS: [GamepadButton; 2] = [GamepadButton::DPadDown, GamepadButton::DPadRight];
+const PREVIOUS_OPTION_BUTTONS: [GamepadButton; 2] =
+    [GamepadButton::DPadUp, GamepadButton::DPadLeft];
+const ACTIVATE_OPTION_BUTTON: GamepadButton = GamepadButton::South;
+const BACK_BUTTON: GamepadButton = GamepadButton::East;
+
+// Faces of the cube as laid out in the color scheme net
+#[derive(Component, Clone, Copy)]
+enum SchemeFace {
+    Up,
+    Down,
+    Front,
+    Back,
+    Right,
+    Left,
+}
+
+// Scheme being edited on the colors screen, only stored once it's saved
+#[derive(Resource)]
+struct ColorSchemeDraft(ColorScheme);
+
+#[derive(Component)]
+struct ColorSchemeWarning;
+
+#[derive(Clone, Copy, Debug)]
+struct ToggleSetting {
+    name: &'static str,
+    states: (&'static str, &'static str), // Shown when it's on, and when it's off
+    value: fn(&Preferences) -> bool,
+    value_mut: fn(&mut Preferences) -> &mut bool,
+}
+
+impl ToggleSetting {
+    fn label(&self, preferences: &Preferences) -> String {
+        let (on, off) = self.states;
+        let state = if (self.value)(preferences) { on } else { off };
+        format!("{}: {state}", self.name)
+    }
+
+    fn toggle(&self, preferences: &mut Preferences) {
+        let value = (self.value_mut)(preferences);
+        *value = !*value;
+    }
+}
+
+#[derive(Component, Debug)]
 enum MenuButtonAction {
     Play,
+    Settings,
+    About,
+    ToggleControlScheme,
+    Toggle(ToggleSetting),
+    ToggleCameraView,
+    ToggleHandedness,
+    ToggleRotationMode,
+    TogglePitchLimit,
+    CycleBorderWidth,
+    ToggleTurnEasing,
+    ToggleSliceConvention,
+    CycleDeadZone,
+    CycleStickerBrightness,
+    CycleStickerSaturation,
+    Colors,
+    SaveColorScheme,
+    ResetToDefaults,
+    ConfirmReset,
+    BackToSettings,
+    BackToMainMenu,
     Quit,
 }
 
@@ -36,13 +126,34 @@ pub(crate) fn plugin(app: &mut App) {
     app.add_sub_state::<MenuState>()
         .add_systems(OnEnter(GameState::Menu), main_menu_pre_setup)
         .add_systems(OnEnter(MenuState::Main), main_menu_setup)
+        .add_systems(OnEnter(MenuState::Settings), settings_menu_setup)
+        .add_systems(OnEnter(MenuState::ConfirmReset), confirm_reset_setup)
+        .add_systems(OnEnter(MenuState::About), about_setup)
+        .add_systems(OnEnter(MenuState::Colors), color_scheme_setup)
         .add_systems(
             Update,
-            (menu_action, button_system).run_if(in_state(GameState::Menu)),
+            (
+                (menu_navigation_system, menu_back_system),
+                (settings_scroll_system, scroll_to_selected_setting_system),
+                menu_action,
+                button_system,
+                update_setting_labels.run_if(resource_changed::<Preferences>),
+                color_cell_system,
+                update_color_scheme_preview.run_if(resource_exists_and_changed::<ColorSchemeDraft>),
+            )
+                .run_if(in_state(GameState::Menu)),
         )
-        .add_systems(OnExit(MenuState::Main), cleanup_main_menu_screen);
+        .add_systems(OnExit(MenuState::Main), cleanup_menu_screen)
+        .add_systems(OnExit(MenuState::Settings), cleanup_menu_screen)
+        .add_systems(OnExit(MenuState::ConfirmReset), cleanup_menu_screen)
+        .add_systems(OnExit(MenuState::About), cleanup_menu_screen)
+        .add_systems(
+            OnExit(MenuState::Colors),
+            (cleanup_menu_screen, remove_color_scheme_draft),
+        );
 }
 
+#[allow(clippy::type_complexity)]
 fn button_system(
     mut interaction_query: Query<
         (
@@ -103,7 +214,7 @@ fn button_system(
     }
 }
 
-fn cleanup_main_menu_screen(mut _commands: Commands, mut clear_color: ResMut<ClearColor>) {
+fn cleanup_menu_screen(mut _commands: Commands, mut clear_color: ResMut<ClearColor>) {
     clear_color.0 = ClearColor::default().0;
 }
 
@@ -111,15 +222,8 @@ fn main_menu_pre_setup(mut commands: Commands) {
     commands.spawn((DespawnOnExit(GameState::Menu), Camera2d));
 }
 
-fn main_menu_setup(
-    mut commands: Commands,
-    mut clear_color: ResMut<ClearColor>,
-    _asset_server: Res<AssetServer>,
-    font_family: Res<MainFont>,
-) {
-    let font_family = &font_family.0;
-
-    let button_node = Node {
+fn button_node() -> Node {
+    Node {
         width: px(300),
         height: px(65),
         margin: UiRect::all(px(20)),
@@ -127,17 +231,55 @@ fn main_menu_setup(
         align_items: AlignItems::Center,
         border: UiRect::all(px(2)),
         ..default()
-    };
-    let button_text_font = TextFont {
+    }
+}
+
+fn button_text_font(font_family: &Handle<Font>) -> TextFont {
+    TextFont {
         font_size: 33.0,
         font: font_family.clone(),
         ..default()
-    };
+    }
+}
 
-    clear_color.0 = CLEAR_COLOR;
+fn menu_button(
+    action: MenuButtonAction,
+    label: impl Into<String>,
+    font_family: &Handle<Font>,
+) -> impl Bundle {
+    (
+        Button,
+        button_node(),
+        BackgroundColor(NORMAL_BUTTON),
+        BorderColor::all(TEXT_COLOR),
+        action,
+        children![(
+            Text::new(label),
+            button_text_font(font_family),
+            TextColor(TEXT_COLOR),
+        )],
+    )
+}
 
-    commands.spawn((
-        DespawnOnExit(MenuState::Main),
+fn menu_title(title: impl Into<String>, font_family: &Handle<Font>) -> impl Bundle {
+    (
+        Text::new(title),
+        TextFont {
+            font_size: 67.0,
+            font: font_family.clone(),
+            ..default()
+        },
+        TextColor(TEXT_COLOR),
+        Node {
+            margin: UiRect::all(px(50)),
+            ..default()
+        },
+    )
+}
+
+fn menu_screen(state: MenuState, content: impl Bundle) -> impl Bundle {
+    (
+        DespawnOnExit(state),
         Node {
             width: percent(100),
             height: percent(100),
@@ -145,83 +287,1273 @@ fn main_menu_setup(
             justify_content: JustifyContent::Center,
             ..default()
         },
-        OnMainMenuScreen,
         children![(
             Node {
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 ..default()
             },
+            content,
+        )],
+    )
+}
+
+fn main_menu_setup(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    font_family: Res<MainFont>,
+) {
+    let font_family = &font_family.0;
+
+    clear_color.0 = CLEAR_COLOR;
+
+    commands.spawn((
+        OnMainMenuScreen,
+        menu_screen(
+            MenuState::Main,
             children![
-                (
-                    Text::new("Ruvik"),
-                    TextFont {
-                        font_size: 67.0,
-                        font: font_family.clone(),
-                        ..default()
-                    },
-                    TextColor(TEXT_COLOR),
-                    Node {
-                        margin: UiRect::all(px(50)),
-                        ..default()
-                    },
-                ),
-                (
-                    Button,
-                    button_node.clone(),
-                    BackgroundColor(NORMAL_BUTTON),
-                    BorderColor::all(TEXT_COLOR),
-                    MenuButtonAction::Play,
-                    children![(
-                        Text::new("New Game"),
-                        button_text_font.clone(),
-                        TextColor(TEXT_COLOR),
-                    ),]
-                ),
-                /*(
-                    Button,
-                    button_node.clone(),
-                    BackgroundColor(NORMAL_BUTTON),
-                    MenuButtonAction::Settings,
-                    children![
-                        (
-                            Text::new("Settings"),
-                            button_text_font.clone(),
-                            TextColor(TEXT_COLOR),
-                        ),
-                    ]
-                ),*/
-                (
-                    Button,
-                    button_node,
-                    BackgroundColor(NORMAL_BUTTON),
-                    BorderColor::all(TEXT_COLOR),
-                    MenuButtonAction::Quit,
-                    children![(Text::new("Quit"), button_text_font, TextColor(TEXT_COLOR),),]
+                menu_title("Ruvik", font_family),
+                menu_button(MenuButtonAction::Play, "New Game", font_family),
+                menu_button(MenuButtonAction::Settings, "Settings", font_family),
+                menu_button(MenuButtonAction::About, "About", font_family),
+                menu_button(MenuButtonAction::Quit, "Quit", font_family),
+            ],
+        ),
+    ));
+}
+
+const PITCH_LIMIT_DEGREES: f32 = 80.0;
+
+const DEAD_ZONES_PX: [f32; 4] = [0.0, 4.0, 8.0, 16.0];
+
+// Sticker brightness and saturation levels, from untouched down to StickerCalibration::MIN
+const STICKER_CALIBRATION_LEVELS: [f32; 4] = [1.0, 0.9, 0.8, 0.7];
+
+// Border widths offered in settings, None is the bundled texture
+const BORDER_WIDTHS: [(Option<f32>, &str); 4] = [
+    (None, "Default"),
+    (Some(0.02), "Thin"),
+    (Some(0.07), "Thick"),
+    (Some(0.12), "Chunky"),
+];
+
+// Settings that are either on or off, each the button's label and the preference it flips
+const OPPOSITE_LAYER_MODIFIER: ToggleSetting = ToggleSetting {
+    name: "Alt turns",
+    states: ("Opposite", "Off"),
+    value: |preferences| preferences.opposite_layer_modifier,
+    value_mut: |preferences| &mut preferences.opposite_layer_modifier,
+};
+
+const WIDE_TURN_MODIFIER: ToggleSetting = ToggleSetting {
+    name: "Shift turns",
+    states: ("Wide", "Off"),
+    value: |preferences| preferences.wide_turn_modifier,
+    value_mut: |preferences| &mut preferences.wide_turn_modifier,
+};
+
+const LAYER_MOMENTUM: ToggleSetting = ToggleSetting {
+    name: "Momentum",
+    states: ("On", "Off"),
+    value: |preferences| preferences.layer_momentum,
+    value_mut: |preferences| &mut preferences.layer_momentum,
+};
+
+const INVERT_Y: ToggleSetting = ToggleSetting {
+    name: "Invert Y",
+    states: ("On", "Off"),
+    value: |preferences| preferences.invert_rotation_y,
+    value_mut: |preferences| &mut preferences.invert_rotation_y,
+};
+
+const DETENT_FEEDBACK: ToggleSetting = ToggleSetting {
+    name: "Detents",
+    states: ("On", "Off"),
+    value: |preferences| preferences.detent_feedback,
+    value_mut: |preferences| &mut preferences.detent_feedback,
+};
+
+const AUTO_ALIGN: ToggleSetting = ToggleSetting {
+    name: "Auto-align",
+    states: ("On", "Off"),
+    value: |preferences| preferences.auto_align,
+    value_mut: |preferences| &mut preferences.auto_align,
+};
+
+const FREE_ROTATION: ToggleSetting = ToggleSetting {
+    name: "Free rotation",
+    states: ("On", "Off"),
+    value: |preferences| preferences.free_rotation,
+    value_mut: |preferences| &mut preferences.free_rotation,
+};
+
+const DRAG_ASSIST: ToggleSetting = ToggleSetting {
+    name: "Drag assist",
+    states: ("On", "Off"),
+    value: |preferences| preferences.drag_assist,
+    value_mut: |preferences| &mut preferences.drag_assist,
+};
+
+const CONFIRM_TURNS: ToggleSetting = ToggleSetting {
+    name: "Confirm turns",
+    states: ("On", "Off"),
+    value: |preferences| preferences.confirm_turns,
+    value_mut: |preferences| &mut preferences.confirm_turns,
+};
+
+const EASY_TURNS: ToggleSetting = ToggleSetting {
+    name: "Easy turns",
+    states: ("On", "Off"),
+    value: |preferences| preferences.easy_turns,
+    value_mut: |preferences| &mut preferences.easy_turns,
+};
+
+const FACE_LABELS: ToggleSetting = ToggleSetting {
+    name: "Face labels",
+    states: ("On", "Off"),
+    value: |preferences| preferences.show_face_labels,
+    value_mut: |preferences| &mut preferences.show_face_labels,
+};
+
+const TURN_HINTS: ToggleSetting = ToggleSetting {
+    name: "Turn hints",
+    states: ("On", "Off"),
+    value: |preferences| preferences.turn_hints,
+    value_mut: |preferences| &mut preferences.turn_hints,
+};
+
+const SCROLL_TURNS: ToggleSetting = ToggleSetting {
+    name: "Scroll turns",
+    states: ("On", "Off"),
+    value: |preferences| preferences.scroll_turns,
+    value_mut: |preferences| &mut preferences.scroll_turns,
+};
+
+const GHOST_PREVIEW: ToggleSetting = ToggleSetting {
+    name: "Landing preview",
+    states: ("On", "Off"),
+    value: |preferences| preferences.ghost_preview,
+    value_mut: |preferences| &mut preferences.ghost_preview,
+};
+
+const HIDE_CURSOR: ToggleSetting = ToggleSetting {
+    name: "Cursor while dragging",
+    states: ("Hidden", "Shown"),
+    value: |preferences| preferences.hide_cursor_while_dragging,
+    value_mut: |preferences| &mut preferences.hide_cursor_while_dragging,
+};
+
+const CUSTOM_CURSOR: ToggleSetting = ToggleSetting {
+    name: "Cursor",
+    states: ("Custom", "System"),
+    value: |preferences| preferences.custom_cursor,
+    value_mut: |preferences| &mut preferences.custom_cursor,
+};
+
+const X_RAY: ToggleSetting = ToggleSetting {
+    name: "X-ray",
+    states: ("On", "Off"),
+    value: |preferences| preferences.cube_style.x_ray,
+    value_mut: |preferences| &mut preferences.cube_style.x_ray,
+};
+
+const OUTLINES: ToggleSetting = ToggleSetting {
+    name: "Outlines",
+    states: ("On", "Off"),
+    value: |preferences| preferences.cube_style.outlines,
+    value_mut: |preferences| &mut preferences.cube_style.outlines,
+};
+
+// Text for buttons that show the current value of a setting
+fn setting_label(action: &MenuButtonAction, preferences: &Preferences) -> Option<String> {
+    match action {
+        MenuButtonAction::Toggle(setting) => Some(setting.label(preferences)),
+        MenuButtonAction::ToggleControlScheme => Some(
+            match preferences.control_scheme {
+                ControlScheme::Drag => "Controls: Drag",
+                ControlScheme::SelectAndTurn => "Controls: Arrows",
+            }
+            .to_string(),
+        ),
+        MenuButtonAction::ToggleRotationMode => Some(
+            match preferences.cube_rotation_mode {
+                CubeRotationMode::Turntable => "Rotation: Turntable",
+                CubeRotationMode::Trackball => "Rotation: Trackball",
+            }
+            .to_string(),
+        ),
+        MenuButtonAction::TogglePitchLimit => Some(match preferences.max_cube_pitch_degrees {
+            Some(max_pitch) => format!("Pitch limit: {max_pitch:.0}°"),
+            None => "Pitch limit: Off".to_string(),
+        }),
+        MenuButtonAction::CycleBorderWidth => Some(format!(
+            "Borders: {}",
+            BORDER_WIDTHS
+                .iter()
+                .find(|(border_width, _)| *border_width == preferences.cube_style.border_width)
+                .map_or("Custom", |(_, name)| name)
+        )),
+        MenuButtonAction::ToggleTurnEasing => Some(
+            match preferences.turn_easing {
+                TurnEasing::Linear => "Turn easing: Linear",
+                TurnEasing::Bounce => "Turn easing: Bounce",
+            }
+            .to_string(),
+        ),
+        MenuButtonAction::CycleDeadZone => Some(format!(
+            "Dead zone: {:.0} px",
+            preferences.drag_dead_zone_px
+        )),
+        MenuButtonAction::CycleStickerBrightness => Some(format!(
+            "Sticker brightness: {:.0}%",
+            preferences.sticker_calibration.brightness * 100.0
+        )),
+        MenuButtonAction::CycleStickerSaturation => Some(format!(
+            "Sticker saturation: {:.0}%",
+            preferences.sticker_calibration.saturation * 100.0
+        )),
+        MenuButtonAction::ToggleCameraView => Some(
+            match preferences.camera_view {
+                CameraView::HeadOn => "Camera: Head-on",
+                CameraView::Corner => "Camera: Corner",
+            }
+            .to_string(),
+        ),
+        MenuButtonAction::ToggleHandedness => Some(
+            match preferences.handedness {
+                Handedness::Right => "Handedness: Right",
+                Handedness::Left => "Handedness: Left",
+            }
+            .to_string(),
+        ),
+        MenuButtonAction::ToggleSliceConvention => Some(
+            match preferences.slice_convention {
+                SliceConvention::Standard => "Slices: M like L",
+                SliceConvention::Mirrored => "Slices: M like R",
+            }
+            .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+// Lays the setting buttons out three per row, scrolling the rows that don't fit on screen
+fn settings_grid_node() -> Node {
+    Node {
+        width: px(960),
+        max_height: vh(65),
+        flex_wrap: FlexWrap::Wrap,
+        justify_content: JustifyContent::Center,
+        align_content: AlignContent::FlexStart,
+        overflow: Overflow::scroll_y(),
+        ..default()
+    }
+}
+
+fn setting_button(
+    action: MenuButtonAction,
+    preferences: &Preferences,
+    font_family: &Handle<Font>,
+) -> impl Bundle {
+    let label = setting_label(&action, preferences).unwrap_or_default();
+    settings_grid_button(action, label, font_family)
+}
+
+// Smaller than menu_button so the grid of settings fits on screen
+fn settings_grid_button(
+    action: MenuButtonAction,
+    label: impl Into<String>,
+    font_family: &Handle<Font>,
+) -> impl Bundle {
+    (
+        Button,
+        Node {
+            height: px(50),
+            margin: UiRect::all(px(8)),
+            ..button_node()
+        },
+        BackgroundColor(NORMAL_BUTTON),
+        BorderColor::all(TEXT_COLOR),
+        action,
+        children![(
+            Text::new(label),
+            TextFont {
+                font_size: 26.0,
+                font: font_family.clone(),
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+        )],
+    )
+}
+
+fn settings_menu_setup(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    font_family: Res<MainFont>,
+    preferences: Res<Preferences>,
+) {
+    let font_family = &font_family.0;
+
+    clear_color.0 = CLEAR_COLOR;
+
+    commands.spawn(menu_screen(
+        MenuState::Settings,
+        children![
+            menu_title("Settings", font_family),
+            (
+                SettingsGrid,
+                settings_grid_node(),
+                children![
+                    setting_button(
+                        MenuButtonAction::ToggleControlScheme,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::ToggleCameraView,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::ToggleHandedness,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(FREE_ROTATION),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(AUTO_ALIGN),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::ToggleRotationMode,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::TogglePitchLimit,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(INVERT_Y),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(OPPOSITE_LAYER_MODIFIER),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(WIDE_TURN_MODIFIER),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::CycleBorderWidth,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(MenuButtonAction::Toggle(X_RAY), &preferences, font_family),
+                    setting_button(
+                        MenuButtonAction::Toggle(OUTLINES),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::CycleStickerBrightness,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::CycleStickerSaturation,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(LAYER_MOMENTUM),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::ToggleTurnEasing,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(EASY_TURNS),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(CONFIRM_TURNS),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(DRAG_ASSIST),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(DETENT_FEEDBACK),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(FACE_LABELS),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::ToggleSliceConvention,
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(TURN_HINTS),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(SCROLL_TURNS),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(GHOST_PREVIEW),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(MenuButtonAction::CycleDeadZone, &preferences, font_family),
+                    setting_button(
+                        MenuButtonAction::Toggle(HIDE_CURSOR),
+                        &preferences,
+                        font_family
+                    ),
+                    setting_button(
+                        MenuButtonAction::Toggle(CUSTOM_CURSOR),
+                        &preferences,
+                        font_family
+                    ),
+                    settings_grid_button(MenuButtonAction::Colors, "Colors", font_family),
+                ],
+            ),
+            menu_button(
+                MenuButtonAction::ResetToDefaults,
+                "Reset to defaults",
+                font_family
+            ),
+            menu_button(MenuButtonAction::BackToMainMenu, "Back", font_family),
+        ],
+    ));
+}
+
+fn confirm_reset_setup(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    font_family: Res<MainFont>,
+) {
+    let font_family = &font_family.0;
+
+    clear_color.0 = CLEAR_COLOR;
+
+    commands.spawn(menu_screen(
+        MenuState::ConfirmReset,
+        children![
+            (
+                Text::new("Reset all settings to their defaults?"),
+                button_text_font(font_family),
+                TextColor(TEXT_COLOR),
+                Node {
+                    margin: UiRect::all(px(50)),
+                    ..default()
+                },
+            ),
+            menu_button(MenuButtonAction::ConfirmReset, "Reset", font_family),
+            menu_button(MenuButtonAction::BackToSettings, "Cancel", font_family),
+        ],
+    ));
+}
+
+fn about_setup(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    font_family: Res<MainFont>,
+) {
+    let font_family = &font_family.0;
+
+    let info_font = TextFont {
+        font_size: 22.0,
+        font: font_family.clone(),
+        ..default()
+    };
+
+    clear_color.0 = CLEAR_COLOR;
+
+    commands.spawn(menu_screen(
+        MenuState::About,
+        children![
+            menu_title("About", font_family),
+            (
+                Text::new(format!(
+                    "Ruvik {}\nBy {}",
+                    env!("CARGO_PKG_VERSION"),
+                    env!("CARGO_PKG_AUTHORS")
+                )),
+                button_text_font(font_family),
+                TextColor(TEXT_COLOR),
+                TextLayout::new_with_justify(Justify::Center),
+                Node {
+                    margin: UiRect::bottom(px(30)),
+                    ..default()
+                },
+            ),
+            (
+                Text::new(
+                    "Left drag: turn a layer\n\
+                     Scroll: turn the hovered face (when enabled)\n\
+                     Right drag: rotate the cube (when free rotation is on)\n\
+                     1, 2, 3: front, corner and top views (when free rotation is on)\n\
+                     Click + arrow keys: turn the selected layer (arrow controls)\n\
+                     Alt + turn: turn the opposite layer (when enabled)\n\
+                     Shift + turn: turn an outer layer with the middle one (when enabled)\n\
+                     T: toggle stickers\n\
+                     X: x-ray, see the back faces through the cube\n\
+                     E: exploded view, pulls the cubies apart\n\
+                     C: move the cube back to the center\n\
+                     P: cycle color schemes\n\
+                     Z: zen mode, hides everything but the cube\n\
+                     F1: show cube axes\n\
+                     F2: show cubie info on hover\n\
+                     F3: log why clicks don't start a drag\n\
+                     F4: show each cubie's position"
                 ),
-            ]
+                info_font,
+                TextColor(TEXT_COLOR),
+                TextLayout::new_with_justify(Justify::Center),
+            ),
+            menu_button(MenuButtonAction::BackToMainMenu, "Back", font_family),
+        ],
+    ));
+}
+
+impl SchemeFace {
+    fn label(self) -> &'static str {
+        match self {
+            SchemeFace::Up => "U",
+            SchemeFace::Down => "D",
+            SchemeFace::Front => "F",
+            SchemeFace::Back => "B",
+            SchemeFace::Right => "R",
+            SchemeFace::Left => "L",
+        }
+    }
+
+    fn color_mut(self, color_scheme: &mut ColorScheme) -> &mut StickerColor {
+        match self {
+            SchemeFace::Up => &mut color_scheme.pos_y,
+            SchemeFace::Down => &mut color_scheme.neg_y,
+            SchemeFace::Front => &mut color_scheme.pos_z,
+            SchemeFace::Back => &mut color_scheme.neg_z,
+            SchemeFace::Right => &mut color_scheme.pos_x,
+            SchemeFace::Left => &mut color_scheme.neg_x,
+        }
+    }
+
+    fn color(self, color_scheme: &ColorScheme) -> StickerColor {
+        match self {
+            SchemeFace::Up => color_scheme.pos_y,
+            SchemeFace::Down => color_scheme.neg_y,
+            SchemeFace::Front => color_scheme.pos_z,
+            SchemeFace::Back => color_scheme.neg_z,
+            SchemeFace::Right => color_scheme.pos_x,
+            SchemeFace::Left => color_scheme.neg_x,
+        }
+    }
+}
+
+const COLOR_CELL_SIZE: f32 = 70.0;
+
+fn color_cell_node() -> Node {
+    Node {
+        width: px(COLOR_CELL_SIZE),
+        height: px(COLOR_CELL_SIZE),
+        margin: UiRect::all(px(4)),
+        border: UiRect::all(px(2)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+fn color_cell(
+    face: SchemeFace,
+    color_scheme: &ColorScheme,
+    font_family: &Handle<Font>,
+) -> impl Bundle {
+    (
+        face,
+        Interaction::default(),
+        color_cell_node(),
+        BackgroundColor(face.color(color_scheme).color()),
+        BorderColor::all(TEXT_COLOR),
+        children![(
+            Text::new(face.label()),
+            button_text_font(font_family),
+            TextColor(HOVER_TEXT_COLOR),
         )],
+    )
+}
+
+fn color_scheme_setup(
+    mut commands: Commands,
+    mut clear_color: ResMut<ClearColor>,
+    font_family: Res<MainFont>,
+    preferences: Res<Preferences>,
+) {
+    let font_family = &font_family.0;
+    let color_scheme = &preferences.color_scheme;
+
+    clear_color.0 = CLEAR_COLOR;
+
+    commands.insert_resource(ColorSchemeDraft(color_scheme.clone()));
+
+    let row = || Node {
+        flex_direction: FlexDirection::Row,
+        ..default()
+    };
+
+    // Unfolded cube, click a face to change its color
+    commands.spawn(menu_screen(
+        MenuState::Colors,
+        children![
+            menu_title("Colors", font_family),
+            (
+                row(),
+                children![
+                    color_cell_node(),
+                    color_cell(SchemeFace::Up, color_scheme, font_family),
+                    color_cell_node(),
+                    color_cell_node(),
+                ],
+            ),
+            (
+                row(),
+                children![
+                    color_cell(SchemeFace::Left, color_scheme, font_family),
+                    color_cell(SchemeFace::Front, color_scheme, font_family),
+                    color_cell(SchemeFace::Right, color_scheme, font_family),
+                    color_cell(SchemeFace::Back, color_scheme, font_family),
+                ],
+            ),
+            (
+                row(),
+                children![
+                    color_cell_node(),
+                    color_cell(SchemeFace::Down, color_scheme, font_family),
+                    color_cell_node(),
+                    color_cell_node(),
+                ],
+            ),
+            (
+                ColorSchemeWarning,
+                Text::new("Every face needs its own color"),
+                button_text_font(font_family),
+                TextColor(TEXT_COLOR),
+                Visibility::Hidden,
+                Node {
+                    margin: UiRect::top(px(20)),
+                    ..default()
+                },
+            ),
+            menu_button(MenuButtonAction::SaveColorScheme, "Save", font_family),
+            menu_button(MenuButtonAction::BackToSettings, "Cancel", font_family),
+        ],
     ));
 }
 
+fn color_cell_system(
+    mut cells: Query<(
+        Ref<Interaction>,
+        &SchemeFace,
+        &mut BorderColor,
+        Has<SelectedOption>,
+    )>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    draft: Option<ResMut<ColorSchemeDraft>>,
+) {
+    let Some(mut draft) = draft else {
+        return;
+    };
+
+    let activated = option_activated(&keys, &gamepads);
+
+    for (interaction, face, mut border_color, selected) in &mut cells {
+        if interaction.is_changed() {
+            *border_color = match (*interaction, selected) {
+                (Interaction::None, false) => BorderColor::all(TEXT_COLOR),
+                _ => BorderColor::all(HOVER_TEXT_COLOR),
+            };
+        }
+
+        let clicked = interaction.is_changed() && *interaction == Interaction::Pressed;
+        if clicked || (activated && selected) {
+            let color = face.color_mut(&mut draft.0);
+            *color = color.next();
+        }
+    }
+}
+
+fn option_activated(keys: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>) -> bool {
+    keys.any_just_pressed(ACTIVATE_OPTION_KEYS)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(ACTIVATE_OPTION_BUTTON))
+}
+
+// Moves the selection through the buttons and color cells of the screen in reading order
+fn settings_scroll_system(
+    mut wheel_events: MessageReader<MouseWheel>,
+    grid: Single<(&ComputedNode, &mut ScrollPosition), With<SettingsGrid>>,
+) {
+    let (node, mut scroll_position) = grid.into_inner();
+    let scroll: f32 = wheel_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SETTINGS_SCROLL_LINE,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    if scroll == 0.0 {
+        return;
+    }
+
+    scroll_settings_by(node, &mut scroll_position, -scroll);
+}
+
+// Keeps a setting picked with the keyboard or a gamepad in view
+fn scroll_to_selected_setting_system(
+    selected: Query<(&UiGlobalTransform, &ComputedNode, &ChildOf), Added<SelectedOption>>,
+    grid: Single<
+        (
+            Entity,
+            &UiGlobalTransform,
+            &ComputedNode,
+            &mut ScrollPosition,
+        ),
+        With<SettingsGrid>,
+    >,
+) {
+    let (grid_entity, grid_transform, grid_node, mut scroll_position) = grid.into_inner();
+    for (transform, node, child_of) in &selected {
+        if child_of.parent() != grid_entity {
+            continue;
+        }
+
+        // Both in physical pixels, measured from the grid's center
+        let offset = transform.translation.y - grid_transform.translation.y;
+        let room = (grid_node.size().y - node.size().y).max(0.0) / 2.0;
+        let overflow = offset - offset.clamp(-room, room);
+        if overflow != 0.0 {
+            scroll_settings_by(
+                grid_node,
+                &mut scroll_position,
+                overflow * grid_node.inverse_scale_factor(),
+            );
+        }
+    }
+}
+
+// Layout clamps what it shows but not the component, so a long scroll past the end would
+// otherwise have to be scrolled back before the grid moves again
+fn scroll_settings_by(node: &ComputedNode, scroll_position: &mut ScrollPosition, amount: f32) {
+    let max_scroll = (node.content_size().y - node.size().y).max(0.0) * node.inverse_scale_factor();
+    scroll_position.y = (scroll_position.y + amount).clamp(0.0, max_scroll);
+}
+
+#[allow(clippy::type_complexity)]
+fn menu_navigation_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut options: Query<
+        (
+            Entity,
+            &UiGlobalTransform,
+            &mut Interaction,
+            Has<SelectedOption>,
+        ),
+        Or<(With<Button>, With<SchemeFace>)>,
+    >,
+) {
+    // Tab goes forwards, Shift+Tab backwards
+    let tab = keys.just_pressed(TAB_KEY);
+    let shift = keys.any_pressed(SHIFT_KEYS);
+
+    let next = keys.any_just_pressed(NEXT_OPTION_KEYS)
+        || (tab && !shift)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.any_just_pressed(NEXT_OPTION_BUTTONS));
+    let previous = keys.any_just_pressed(PREVIOUS_OPTION_KEYS)
+        || (tab && shift)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.any_just_pressed(PREVIOUS_OPTION_BUTTONS));
+
+    if next == previous {
+        return;
+    }
+
+    let mut order = options
+        .iter()
+        .map(|(entity, transform, _, selected)| (entity, transform.translation, selected))
+        .collect::<Vec<_>>();
+    order.sort_by(|(entity_a, position_a, _), (entity_b, position_b, _)| {
+        position_a
+            .y
+            .total_cmp(&position_b.y)
+            .then(position_a.x.total_cmp(&position_b.x))
+            .then(entity_a.index().cmp(&entity_b.index()))
+    });
+
+    if order.is_empty() {
+        return;
+    }
+
+    let current = order.iter().position(|(_, _, selected)| *selected);
+    let target = match (current, next) {
+        (None, true) => 0,
+        (None, false) => order.len() - 1,
+        (Some(index), true) => (index + 1) % order.len(),
+        (Some(index), false) => (index + order.len() - 1) % order.len(),
+    };
+
+    for (index, (entity, _, selected)) in order.into_iter().enumerate() {
+        if (index == target) == selected {
+            continue;
+        }
+
+        if index == target {
+            commands.entity(entity).insert(SelectedOption);
+        } else {
+            commands.entity(entity).remove::<SelectedOption>();
+        }
+
+        // Restyles the option
+        if let Ok((_, _, mut interaction, _)) = options.get_mut(entity) {
+            interaction.set_changed();
+        }
+    }
+}
+
+fn menu_back_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    current_menu_state: Res<State<MenuState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    if !keys.just_pressed(BACK_KEY)
+        && !gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(BACK_BUTTON))
+    {
+        return;
+    }
+
+    match current_menu_state.get() {
+        MenuState::Main => {}
+        MenuState::Settings | MenuState::About => menu_state.set(MenuState::Main),
+        MenuState::ConfirmReset | MenuState::Colors => menu_state.set(MenuState::Settings),
+    }
+}
+
+fn update_color_scheme_preview(
+    draft: Res<ColorSchemeDraft>,
+    mut cells: Query<(&SchemeFace, &mut BackgroundColor)>,
+    mut warning: Single<&mut Visibility, With<ColorSchemeWarning>>,
+) {
+    for (face, mut background_color) in &mut cells {
+        background_color.0 = face.color(&draft.0).color();
+    }
+
+    warning.set_if_neq(if draft.0.has_distinct_colors() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    });
+}
+
+fn remove_color_scheme_draft(mut commands: Commands) {
+    commands.remove_resource::<ColorSchemeDraft>();
+}
+
+// Next level down, wrapping back to untouched colors after the dimmest
+fn next_calibration_level(current: f32) -> f32 {
+    STICKER_CALIBRATION_LEVELS
+        .iter()
+        .copied()
+        .find(|level| *level < current - 1e-3)
+        .unwrap_or(STICKER_CALIBRATION_LEVELS[0])
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn menu_action(
     interaction_query: Query<
         (&Interaction, &MenuButtonAction),
         (Changed<Interaction>, With<Button>),
     >,
+    selected_query: Query<&MenuButtonAction, With<SelectedOption>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
     mut app_exit_writer: MessageWriter<AppExit>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut preferences: ResMut<Preferences>,
+    color_scheme_draft: Option<Res<ColorSchemeDraft>>,
 ) {
-    for (interaction, menu_button_action) in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            match menu_button_action {
-                MenuButtonAction::Quit => {
-                    app_exit_writer.write(AppExit::Success);
-                }
-                MenuButtonAction::Play => {
-                    game_state.set(GameState::Game);
+    let clicked = interaction_query
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, menu_button_action)| menu_button_action);
+    let activated = option_activated(&keys, &gamepads)
+        .then_some(&selected_query)
+        .into_iter()
+        .flatten();
+
+    for menu_button_action in clicked.chain(activated) {
+        match menu_button_action {
+            MenuButtonAction::Quit => {
+                app_exit_writer.write(AppExit::Success);
+            }
+            MenuButtonAction::Play => {
+                game_state.set(GameState::Game);
+            }
+            MenuButtonAction::Settings | MenuButtonAction::BackToSettings => {
+                menu_state.set(MenuState::Settings);
+            }
+            MenuButtonAction::About => {
+                menu_state.set(MenuState::About);
+            }
+            MenuButtonAction::Toggle(setting) => {
+                setting.toggle(&mut preferences);
+            }
+            MenuButtonAction::ToggleControlScheme => {
+                preferences.control_scheme = match preferences.control_scheme {
+                    ControlScheme::Drag => ControlScheme::SelectAndTurn,
+                    ControlScheme::SelectAndTurn => ControlScheme::Drag,
+                };
+            }
+            MenuButtonAction::ToggleRotationMode => {
+                preferences.cube_rotation_mode = match preferences.cube_rotation_mode {
+                    CubeRotationMode::Turntable => CubeRotationMode::Trackball,
+                    CubeRotationMode::Trackball => CubeRotationMode::Turntable,
+                };
+            }
+            MenuButtonAction::TogglePitchLimit => {
+                preferences.max_cube_pitch_degrees = match preferences.max_cube_pitch_degrees {
+                    Some(_) => None,
+                    None => Some(PITCH_LIMIT_DEGREES),
+                };
+            }
+            MenuButtonAction::CycleBorderWidth => {
+                let current = BORDER_WIDTHS.iter().position(|(border_width, _)| {
+                    *border_width == preferences.cube_style.border_width
+                });
+                let next = current.map_or(0, |index| (index + 1) % BORDER_WIDTHS.len());
+                preferences.cube_style.border_width = BORDER_WIDTHS[next].0;
+            }
+            MenuButtonAction::ToggleTurnEasing => {
+                preferences.turn_easing = match preferences.turn_easing {
+                    TurnEasing::Linear => TurnEasing::Bounce,
+                    TurnEasing::Bounce => TurnEasing::Linear,
+                };
+            }
+            MenuButtonAction::CycleDeadZone => {
+                preferences.drag_dead_zone_px = DEAD_ZONES_PX
+                    .iter()
+                    .copied()
+                    .find(|dead_zone| *dead_zone > preferences.drag_dead_zone_px)
+                    .unwrap_or(DEAD_ZONES_PX[0]);
+            }
+            MenuButtonAction::CycleStickerBrightness => {
+                let calibration = &mut preferences.sticker_calibration;
+                calibration.brightness = next_calibration_level(calibration.brightness);
+            }
+            MenuButtonAction::CycleStickerSaturation => {
+                let calibration = &mut preferences.sticker_calibration;
+                calibration.saturation = next_calibration_level(calibration.saturation);
+            }
+            MenuButtonAction::Colors => {
+                menu_state.set(MenuState::Colors);
+            }
+            MenuButtonAction::SaveColorScheme => {
+                if let Some(draft) = &color_scheme_draft
+                    && draft.0.has_distinct_colors()
+                {
+                    preferences.color_scheme = draft.0.clone();
+                    menu_state.set(MenuState::Settings);
                 }
             }
+            MenuButtonAction::ToggleCameraView => {
+                preferences.camera_view = match preferences.camera_view {
+                    CameraView::HeadOn => CameraView::Corner,
+                    CameraView::Corner => CameraView::HeadOn,
+                };
+            }
+            MenuButtonAction::ToggleHandedness => {
+                preferences.handedness = match preferences.handedness {
+                    Handedness::Right => Handedness::Left,
+                    Handedness::Left => Handedness::Right,
+                };
+            }
+            MenuButtonAction::ToggleSliceConvention => {
+                preferences.slice_convention = match preferences.slice_convention {
+                    SliceConvention::Standard => SliceConvention::Mirrored,
+                    SliceConvention::Mirrored => SliceConvention::Standard,
+                };
+            }
+            MenuButtonAction::ResetToDefaults => {
+                menu_state.set(MenuState::ConfirmReset);
+            }
+            MenuButtonAction::ConfirmReset => {
+                *preferences = Preferences::default();
+                menu_state.set(MenuState::Settings);
+            }
+            MenuButtonAction::BackToMainMenu => {
+                menu_state.set(MenuState::Main);
+            }
+        }
+    }
+}
+
+fn update_setting_labels(
+    preferences: Res<Preferences>,
+    buttons: Query<(&MenuButtonAction, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (action, children) in &buttons {
+        let Some(label) = setting_label(action, &preferences) else {
+            continue;
+        };
+
+        for &child in children {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.0 = label.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    // Menu logic without any rendering, driven with key presses only
+    fn menu_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .init_state::<GameState>()
+            .add_sub_state::<MenuState>()
+            .add_message::<AppExit>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Preferences>()
+            .add_systems(
+                Update,
+                (menu_navigation_system, menu_back_system, menu_action)
+                    .chain()
+                    .run_if(in_state(GameState::Menu)),
+            );
+        app.update();
+        app
+    }
+
+    fn tap(app: &mut App, key: KeyCode) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+        app.update();
+
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+
+        // Lets state changes requested by the press take effect
+        app.update();
+    }
+
+    fn selected_action(app: &mut App) -> Option<String> {
+        app.world_mut()
+            .query_filtered::<&MenuButtonAction, With<SelectedOption>>()
+            .iter(app.world())
+            .map(|action| format!("{action:?}"))
+            .next()
+    }
+
+    fn go_to(app: &mut App, state: MenuState) {
+        app.world_mut()
+            .resource_mut::<NextState<MenuState>>()
+            .set(state);
+        app.update();
+    }
+
+    #[test]
+    fn toggle_settings_flip_their_preference_and_label() {
+        for (setting, off_label, on_label) in [
+            (LAYER_MOMENTUM, "Momentum: Off", "Momentum: On"),
+            (X_RAY, "X-ray: Off", "X-ray: On"),
+            (
+                HIDE_CURSOR,
+                "Cursor while dragging: Shown",
+                "Cursor while dragging: Hidden",
+            ),
+        ] {
+            let mut preferences = Preferences::default();
+            let action = MenuButtonAction::Toggle(setting);
+            assert_eq!(setting_label(&action, &preferences).unwrap(), off_label);
+
+            setting.toggle(&mut preferences);
+            assert!((setting.value)(&preferences));
+            assert_eq!(setting_label(&action, &preferences).unwrap(), on_label);
+
+            setting.toggle(&mut preferences);
+            assert_eq!(setting_label(&action, &preferences).unwrap(), off_label);
         }
     }
+
+    #[test]
+    fn the_settings_grid_scrolls_within_its_contents() {
+        use bevy::input::mouse::MouseScrollUnit;
+
+        let mut app = App::new();
+        app.add_message::<MouseWheel>()
+            .add_systems(Update, settings_scroll_system);
+
+        // Twice as tall as it's shown, on a display with two physical pixels per logical one
+        let grid = app
+            .world_mut()
+            .spawn((
+                SettingsGrid,
+                ComputedNode {
+                    size: Vec2::new(1920.0, 800.0),
+                    content_size: Vec2::new(1920.0, 1600.0),
+                    inverse_scale_factor: 0.5,
+                    ..default()
+                },
+                ScrollPosition::default(),
+            ))
+            .id();
+
+        for (y, expected) in [(-2.0, 132.0), (-10.0, 400.0), (1.0, 334.0), (20.0, 0.0)] {
+            app.world_mut().write_message(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y,
+                window: Entity::PLACEHOLDER,
+            });
+            app.update();
+            assert_eq!(app.world().get::<ScrollPosition>(grid).unwrap().y, expected);
+        }
+    }
+
+    #[test]
+    fn every_main_menu_action_is_reachable_by_keyboard() {
+        let mut app = menu_app();
+        for action in [
+            MenuButtonAction::Play,
+            MenuButtonAction::Settings,
+            MenuButtonAction::About,
+            MenuButtonAction::Quit,
+        ] {
+            app.world_mut().spawn((Button, action));
+        }
+
+        assert_eq!(selected_action(&mut app), None);
+        tap(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected_action(&mut app).as_deref(), Some("Play"));
+
+        // Selection wraps around both ways
+        tap(&mut app, KeyCode::ArrowUp);
+        assert_eq!(selected_action(&mut app).as_deref(), Some("Quit"));
+        tap(&mut app, KeyCode::Tab);
+        assert_eq!(selected_action(&mut app).as_deref(), Some("Play"));
+
+        tap(&mut app, KeyCode::ArrowDown);
+        tap(&mut app, KeyCode::Enter);
+        assert_eq!(
+            *app.world().resource::<State<MenuState>>().get(),
+            MenuState::Settings
+        );
+
+        tap(&mut app, KeyCode::Escape);
+        assert_eq!(
+            *app.world().resource::<State<MenuState>>().get(),
+            MenuState::Main
+        );
+
+        tap(&mut app, KeyCode::ArrowDown);
+        tap(&mut app, KeyCode::Enter);
+        assert_eq!(
+            *app.world().resource::<State<MenuState>>().get(),
+            MenuState::About
+        );
+
+        tap(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected_action(&mut app).as_deref(), Some("Quit"));
+        tap(&mut app, KeyCode::Space);
+        assert!(!app.world().resource::<Messages<AppExit>>().is_empty());
+
+        go_to(&mut app, MenuState::Main);
+        tap(&mut app, KeyCode::ArrowDown);
+        tap(&mut app, KeyCode::NumpadEnter);
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Game
+        );
+    }
+
+    #[test]
+    fn reset_confirmation_works_by_keyboard() {
+        let mut app = menu_app();
+        go_to(&mut app, MenuState::ConfirmReset);
+        for action in [
+            MenuButtonAction::ConfirmReset,
+            MenuButtonAction::BackToSettings,
+        ] {
+            app.world_mut().spawn((Button, action));
+        }
+
+        app.world_mut().resource_mut::<Preferences>().easy_turns = true;
+
+        // Backing out keeps the preferences
+        tap(&mut app, KeyCode::Escape);
+        assert_eq!(
+            *app.world().resource::<State<MenuState>>().get(),
+            MenuState::Settings
+        );
+        assert!(app.world().resource::<Preferences>().easy_turns);
+
+        go_to(&mut app, MenuState::ConfirmReset);
+        tap(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected_action(&mut app).as_deref(), Some("ConfirmReset"));
+        tap(&mut app, KeyCode::Enter);
+        assert_eq!(
+            *app.world().resource::<State<MenuState>>().get(),
+            MenuState::Settings
+        );
+        assert_eq!(
+            *app.world().resource::<Preferences>(),
+            Preferences::default()
+        );
+    }
 }
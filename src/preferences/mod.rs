@@ -0,0 +1,529 @@
+use std::io;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::SliceConvention;
+
+const PREFERENCES_KEY: &str = "preferences";
+
+// Game code reads and writes persisted data only through this trait, so the
+// backend can be swapped per target (filesystem on native, localStorage on web).
+pub(crate) trait PreferenceStore: Send + Sync + 'static {
+    fn read(&self, key: &str) -> io::Result<Option<String>>;
+    fn write(&self, key: &str, contents: &str) -> io::Result<()>;
+}
+
+#[derive(Resource)]
+pub(crate) struct PreferenceStorage(Box<dyn PreferenceStore>);
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Preferences {
+    pub(crate) cube_style: CubeStyle,
+    pub(crate) cube_shape: CubeShape,
+    pub(crate) color_scheme: ColorScheme,
+    pub(crate) sticker_calibration: StickerCalibration,
+    pub(crate) move_cooldown_secs: f32, // Minimum time after a move completes before a new drag can start
+    pub(crate) control_scheme: ControlScheme,
+    pub(crate) handedness: Handedness,
+    pub(crate) camera_view: CameraView,
+    pub(crate) drag_dead_zone_px: f32, // How far the cursor has to move before a press becomes a layer drag
+    pub(crate) drag_assist: bool, // Presses on an edge-on face tilt the cube so the face turns towards the viewer
+    pub(crate) free_rotation: bool, // Right drag rotates the whole cube
+    pub(crate) auto_align: bool, // The face most towards the viewer squares up once free rotation is let go
+    pub(crate) cube_rotation_mode: CubeRotationMode,
+    pub(crate) invert_rotation_y: bool, // Moving the mouse up tilts the cube's top towards the viewer
+    pub(crate) max_cube_pitch_degrees: Option<f32>, // How far the cube can tilt away from upright, None for no limit
+    pub(crate) layer_momentum: bool, // Released layers coast on at the speed they were dragged with
+    pub(crate) turn_easing: TurnEasing,
+    pub(crate) easy_turns: bool, // Any drag that starts a turn commits at least a quarter turn
+    pub(crate) scroll_turns: bool, // The mouse wheel turns the hovered face, off so it stays free for other bindings
+    pub(crate) confirm_turns: bool, // Released turns wait for a click before they're made, Escape puts the layer back
+    pub(crate) detent_feedback: bool, // Pulse the cube when a dragged layer passes a quarter turn
+    pub(crate) opposite_layer_modifier: bool, // Holding Alt while turning targets the opposite parallel layer
+    pub(crate) wide_turn_modifier: bool, // Holding Shift while turning an outer layer turns the middle layer with it
+    pub(crate) zen_mode: bool,           // Hides the HUD, leaving just the cube
+    pub(crate) show_face_labels: bool,   // Notation letters over the visible faces
+    pub(crate) slice_convention: SliceConvention, // Which outer layer M, E and S turn along with when moves are named
+    pub(crate) turn_hints: bool, // Arrows on the hovered sticker showing which ways it can be dragged
+    pub(crate) ghost_preview: bool, // Faint copy of a dragged layer where it would land on release
+    pub(crate) hide_cursor_while_dragging: bool, // The OS cursor disappears while a layer is dragged
+    pub(crate) custom_cursor: bool, // Bundled cursor image in the game instead of the OS one
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct CubeStyle {
+    pub(crate) stickers: StickerStyle,
+    pub(crate) border_width: Option<f32>, // Fraction of a face covered by each border, None for the bundled texture
+    pub(crate) x_ray: bool, // See-through cubies, so the stickers on the back faces show faintly
+    pub(crate) outlines: bool, // Dark outline around every cubie, for a cartoon look
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum StickerStyle {
+    #[default]
+    Stickered,
+    Stickerless, // Solid colored plastic, no border texture
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum CubeShape {
+    #[default]
+    Standard,
+    Mirror, // Single color, outer layers of different thickness
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum ControlScheme {
+    #[default]
+    Drag,
+    SelectAndTurn, // Click a sticker to select it, then turn its row or column with the arrow keys
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum TurnEasing {
+    #[default]
+    Linear,
+    Bounce, // Turns swing slightly past the quarter turn and spring back
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Handedness {
+    #[default]
+    Right,
+    Left, // Layer drags are mirrored left to right, horizontal sweeps turn the other way
+}
+
+// Tones the sticker colors down to suit the display. They're already as bright and saturated as
+// sRGB goes, so both only go down, and not so far that two colors would be hard to tell apart.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct StickerCalibration {
+    pub(crate) brightness: f32,
+    pub(crate) saturation: f32,
+}
+
+impl Default for StickerCalibration {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+impl StickerCalibration {
+    pub(crate) const MIN: f32 = 0.7;
+
+    pub(crate) fn apply(self, color: Color) -> Color {
+        let hsva = Hsva::from(color);
+        let calibrated = hsva
+            .with_saturation(hsva.saturation * self.saturation.clamp(Self::MIN, 1.0))
+            .with_value(hsva.value * self.brightness.clamp(Self::MIN, 1.0));
+        Srgba::from(calibrated).into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum CameraView {
+    #[default]
+    HeadOn,
+    Corner, // From above the front left corner
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum CubeRotationMode {
+    #[default]
+    Turntable, // Horizontal motion yaws, vertical motion pitches
+    Trackball, // Cursor motion rolls a virtual sphere around the cube
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            cube_style: default(),
+            cube_shape: default(),
+            color_scheme: default(),
+            sticker_calibration: default(),
+            move_cooldown_secs: 0.0,
+            control_scheme: default(),
+            handedness: Handedness::Right,
+            camera_view: default(),
+            drag_dead_zone_px: 4.0,
+            drag_assist: false,
+            free_rotation: true,
+            auto_align: false,
+            cube_rotation_mode: default(),
+            invert_rotation_y: false,
+            max_cube_pitch_degrees: None,
+            layer_momentum: false,
+            turn_easing: TurnEasing::Linear,
+            easy_turns: false,
+            scroll_turns: false,
+            confirm_turns: false,
+            detent_feedback: false,
+            opposite_layer_modifier: false,
+            wide_turn_modifier: false,
+            zen_mode: false,
+            show_face_labels: false,
+            slice_convention: default(),
+            turn_hints: false,
+            ghost_preview: false,
+            hide_cursor_while_dragging: false,
+            custom_cursor: false,
+        }
+    }
+}
+
+// Sticker color of each face of a solved cube, in the cube's own axes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ColorScheme {
+    pub(crate) pos_x: StickerColor,
+    pub(crate) neg_x: StickerColor,
+    pub(crate) pos_y: StickerColor,
+    pub(crate) neg_y: StickerColor,
+    pub(crate) pos_z: StickerColor,
+    pub(crate) neg_z: StickerColor,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum StickerColor {
+    White,
+    Yellow,
+    Green,
+    Blue,
+    Red,
+    Orange,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            pos_x: StickerColor::White,
+            neg_x: StickerColor::Yellow,
+            pos_y: StickerColor::Green,
+            neg_y: StickerColor::Blue,
+            pos_z: StickerColor::Red,
+            neg_z: StickerColor::Orange,
+        }
+    }
+}
+
+impl ColorScheme {
+    pub(crate) fn faces(&self) -> [StickerColor; 6] {
+        [
+            self.pos_x, self.neg_x, self.pos_y, self.neg_y, self.pos_z, self.neg_z,
+        ]
+    }
+
+    // A scheme where two faces share a color can't be told apart from a scrambled cube
+    pub(crate) fn has_distinct_colors(&self) -> bool {
+        let faces = self.faces();
+        faces
+            .iter()
+            .enumerate()
+            .all(|(i, color)| !faces[i + 1..].contains(color))
+    }
+}
+
+impl StickerColor {
+    pub(crate) const ALL: [StickerColor; 6] = [
+        StickerColor::White,
+        StickerColor::Yellow,
+        StickerColor::Green,
+        StickerColor::Blue,
+        StickerColor::Red,
+        StickerColor::Orange,
+    ];
+
+    pub(crate) fn color(self) -> Color {
+        match self {
+            StickerColor::White => Color::srgb(1.0, 1.0, 1.0),
+            StickerColor::Yellow => Color::srgb(1.0, 1.0, 0.0),
+            StickerColor::Green => Color::srgb(0.0, 1.0, 0.0),
+            StickerColor::Blue => Color::srgb(0.0, 0.0, 1.0),
+            StickerColor::Red => Color::srgb(1.0, 0.0, 0.0),
+            StickerColor::Orange => Color::srgb(1.0, 0.2, 0.0),
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            StickerColor::White => "white",
+            StickerColor::Yellow => "yellow",
+            StickerColor::Green => "green",
+            StickerColor::Blue => "blue",
+            StickerColor::Red => "red",
+            StickerColor::Orange => "orange",
+        }
+    }
+
+    pub(crate) fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|color| *color == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+pub(crate) fn plugin(app: &mut App) {
+    let storage = PreferenceStorage::default();
+    let preferences = storage
+        .load::<Preferences>(PREFERENCES_KEY)
+        .unwrap_or_default();
+
+    app.insert_resource(preferences)
+        .insert_resource(storage)
+        .add_systems(
+            PostUpdate,
+            save_preferences
+                .run_if(resource_changed::<Preferences>.and(not(resource_added::<Preferences>))),
+        )
+        .add_systems(Last, save_preferences_on_exit);
+}
+
+impl PreferenceStorage {
+    pub(crate) fn load<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Option<T> {
+        let contents = match self.0.read(key) {
+            Ok(Some(contents)) => contents,
+            Ok(None) => return None,
+            Err(error) => {
+                warn!("Failed to read stored {key}: {error}");
+                return None;
+            }
+        };
+
+        match ron::from_str(&contents) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                warn!("Ignoring malformed stored {key}: {error}");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn save<T: Serialize>(&self, key: &str, value: &T) {
+        let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("Failed to serialize {key}: {error}");
+                return;
+            }
+        };
+
+        if let Err(error) = self.0.write(key, &contents) {
+            warn!("Failed to store {key}: {error}");
+        }
+    }
+}
+
+impl Default for PreferenceStorage {
+    fn default() -> Self {
+        #[cfg(feature = "wasm")]
+        let store = web::LocalStorageStore;
+        #[cfg(not(feature = "wasm"))]
+        let store = native::FileStore::new();
+
+        Self(Box::new(store))
+    }
+}
+
+fn save_preferences(storage: Res<PreferenceStorage>, preferences: Res<Preferences>) {
+    storage.save(PREFERENCES_KEY, &*preferences);
+}
+
+// Changes are normally saved as they're made, this catches any whose save failed before the app
+// closes. Nothing is written when the stored copy is already up to date.
+fn save_preferences_on_exit(
+    mut app_exits: MessageReader<AppExit>,
+    storage: Res<PreferenceStorage>,
+    preferences: Res<Preferences>,
+) {
+    if app_exits.read().last().is_none() {
+        return;
+    }
+
+    if storage.load::<Preferences>(PREFERENCES_KEY).as_ref() != Some(&*preferences) {
+        storage.save(PREFERENCES_KEY, &*preferences);
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+mod native {
+    use std::{env, fs, io, path::PathBuf};
+
+    use super::PreferenceStore;
+
+    pub(crate) struct FileStore {
+        directory: PathBuf,
+    }
+
+    impl FileStore {
+        pub(crate) fn new() -> Self {
+            let config_directory = env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+                .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+                .unwrap_or_default();
+
+            Self {
+                directory: config_directory.join("ruvik"),
+            }
+        }
+
+        fn path(&self, key: &str) -> PathBuf {
+            self.directory.join(format!("{key}.ron"))
+        }
+    }
+
+    impl PreferenceStore for FileStore {
+        fn read(&self, key: &str) -> io::Result<Option<String>> {
+            match fs::read_to_string(self.path(key)) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(error),
+            }
+        }
+
+        fn write(&self, key: &str, contents: &str) -> io::Result<()> {
+            fs::create_dir_all(&self.directory)?;
+            fs::write(self.path(key), contents)
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod web {
+    use std::io;
+
+    use super::PreferenceStore;
+
+    pub(crate) struct LocalStorageStore;
+
+    impl LocalStorageStore {
+        fn storage() -> io::Result<web_sys::Storage> {
+            web_sys::window()
+                .and_then(|window| window.local_storage().ok().flatten())
+                .ok_or_else(|| io::Error::other("localStorage is unavailable"))
+        }
+
+        fn item_key(key: &str) -> String {
+            format!("ruvik.{key}")
+        }
+    }
+
+    impl PreferenceStore for LocalStorageStore {
+        fn read(&self, key: &str) -> io::Result<Option<String>> {
+            Self::storage()?
+                .get_item(&Self::item_key(key))
+                .map_err(|_| io::Error::other("failed to read from localStorage"))
+        }
+
+        fn write(&self, key: &str, contents: &str) -> io::Result<()> {
+            Self::storage()?
+                .set_item(&Self::item_key(key), contents)
+                .map_err(|_| io::Error::other("failed to write to localStorage"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    // Keeps what's written in memory and counts the writes
+    #[derive(Clone, Default)]
+    struct MemoryStore(Arc<Mutex<(Option<String>, usize)>>);
+
+    impl PreferenceStore for MemoryStore {
+        fn read(&self, _key: &str) -> io::Result<Option<String>> {
+            Ok(self.0.lock().unwrap().0.clone())
+        }
+
+        fn write(&self, _key: &str, contents: &str) -> io::Result<()> {
+            let mut stored = self.0.lock().unwrap();
+            *stored = (Some(contents.to_string()), stored.1 + 1);
+            Ok(())
+        }
+    }
+
+    fn exit_app(store: &MemoryStore, preferences: Preferences) -> App {
+        let mut app = App::new();
+        app.add_message::<AppExit>()
+            .insert_resource(PreferenceStorage(Box::new(store.clone())))
+            .insert_resource(preferences)
+            .add_systems(Last, save_preferences_on_exit);
+        app
+    }
+
+    #[test]
+    fn preferences_are_flushed_on_exit_only_when_out_of_date() {
+        let store = MemoryStore::default();
+        let preferences = Preferences {
+            zen_mode: true,
+            ..default()
+        };
+        let mut app = exit_app(&store, preferences.clone());
+
+        // Nothing happens until the app is closing
+        app.update();
+        assert_eq!(store.0.lock().unwrap().1, 0);
+
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        assert_eq!(store.0.lock().unwrap().1, 1);
+        assert_eq!(
+            PreferenceStorage(Box::new(store.clone())).load::<Preferences>(PREFERENCES_KEY),
+            Some(preferences.clone())
+        );
+
+        // Already stored, so closing again writes nothing
+        let mut app = exit_app(&store, preferences);
+        app.world_mut().write_message(AppExit::Success);
+        app.update();
+        assert_eq!(store.0.lock().unwrap().1, 1);
+    }
+
+    #[test]
+    fn calibrated_sticker_colors_stay_apart() {
+        let distance = |a: Color, b: Color| {
+            let (a, b) = (a.to_srgba(), b.to_srgba());
+            Vec3::new(a.red - b.red, a.green - b.green, a.blue - b.blue).length()
+        };
+
+        let dimmest = StickerCalibration {
+            brightness: StickerCalibration::MIN,
+            saturation: StickerCalibration::MIN,
+        };
+        // Hand-edited values can't go past the dimmest level
+        let washed_out = StickerCalibration {
+            brightness: 0.0,
+            saturation: 0.0,
+        };
+        assert_eq!(
+            washed_out.apply(StickerColor::Red.color()),
+            dimmest.apply(StickerColor::Red.color())
+        );
+
+        for (i, a) in StickerColor::ALL.into_iter().enumerate() {
+            assert_eq!(StickerCalibration::default().apply(a.color()), a.color());
+
+            for b in &StickerColor::ALL[i + 1..] {
+                let untouched = distance(a.color(), b.color());
+                let calibrated = distance(dimmest.apply(a.color()), dimmest.apply(b.color()));
+                assert!(
+                    calibrated > 0.4 * untouched,
+                    "{} and {} end up {calibrated} apart",
+                    a.name(),
+                    b.name()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+const FADE_COLOR: Color = Color::BLACK;
+const FADE_DURATION_SECS: f32 = 0.35;
+
+#[derive(Component)]
+struct ScreenFade(Timer);
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Menu), spawn_screen_fade)
+        .add_systems(OnEnter(GameState::Game), spawn_screen_fade)
+        .add_systems(Update, screen_fade_system);
+}
+
+fn spawn_screen_fade(mut commands: Commands, state: Res<State<GameState>>) {
+    // Purely visual overlay, input keeps reaching whatever is underneath while it fades
+    commands.spawn((
+        DespawnOnExit(*state.get()),
+        ScreenFade(Timer::from_seconds(FADE_DURATION_SECS, TimerMode::Once)),
+        Node {
+            position_type: PositionType::Absolute,
+            width: percent(100),
+            height: percent(100),
+            ..default()
+        },
+        BackgroundColor(FADE_COLOR),
+        GlobalZIndex(i32::MAX),
+        FocusPolicy::Pass,
+        Pickable::IGNORE,
+    ));
+}
+
+fn screen_fade_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fades: Query<(Entity, &mut ScreenFade, &mut BackgroundColor)>,
+) {
+    for (entity, mut fade, mut background_color) in &mut fades {
+        fade.0.tick(time.delta());
+
+        background_color.0 = FADE_COLOR.with_alpha(1.0 - fade.0.fraction());
+
+        if fade.0.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
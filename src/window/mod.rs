@@ -0,0 +1,91 @@
+use bevy::{
+    asset::AssetLoadFailedEvent, ecs::system::NonSendMarker, prelude::*, window::PrimaryWindow,
+    winit::WINIT_WINDOWS,
+};
+use winit::window::Icon;
+
+// Icon asset the window was configured with, until it's loaded and set
+#[derive(Resource)]
+pub(crate) struct WindowIcon {
+    path: String,
+    handle: Option<Handle<Image>>,
+}
+
+impl WindowIcon {
+    pub(crate) fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            handle: None,
+        }
+    }
+}
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(Startup, window_icon_setup).add_systems(
+        Update,
+        window_icon_system.run_if(resource_exists::<WindowIcon>),
+    );
+}
+
+fn window_icon_setup(asset_server: Res<AssetServer>, icon: Option<ResMut<WindowIcon>>) {
+    if let Some(mut icon) = icon {
+        icon.handle = Some(asset_server.load(icon.path.clone()));
+    }
+}
+
+// Sets the icon once it's loaded and the OS window exists. An icon that can't be loaded or used
+// only costs a warning, the window keeps the platform's default icon.
+fn window_icon_system(
+    mut commands: Commands,
+    mut load_failures: MessageReader<AssetLoadFailedEvent<Image>>,
+    icon: Res<WindowIcon>,
+    images: Res<Assets<Image>>,
+    window: Single<Entity, With<PrimaryWindow>>,
+    _main_thread: NonSendMarker,
+) {
+    let Some(handle) = &icon.handle else {
+        return;
+    };
+
+    for failure in load_failures.read() {
+        if failure.id == handle.id() {
+            warn!(
+                "Failed to load the window icon {}: {}",
+                failure.path, failure.error
+            );
+            commands.remove_resource::<WindowIcon>();
+            return;
+        }
+    }
+
+    let Some(image) = images.get(handle) else {
+        return;
+    };
+
+    let applied = WINIT_WINDOWS.with_borrow(|winit_windows| {
+        let Some(winit_window) = winit_windows.get_window(*window) else {
+            return false; // Not created yet, try again next frame
+        };
+
+        match window_icon(image) {
+            Ok(winit_icon) => winit_window.set_window_icon(Some(winit_icon)),
+            Err(error) => warn!("Can't use {} as the window icon: {error}", icon.path),
+        }
+        true
+    });
+
+    if applied {
+        commands.remove_resource::<WindowIcon>();
+    }
+}
+
+fn window_icon(image: &Image) -> Result<Icon, String> {
+    let rgba = image
+        .clone()
+        .try_into_dynamic()
+        .map_err(|error| error.to_string())?
+        .into_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    Icon::from_rgba(rgba.into_raw(), width, height).map_err(|error| error.to_string())
+}